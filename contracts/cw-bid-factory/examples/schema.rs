@@ -0,0 +1,10 @@
+use cosmwasm_schema::write_api;
+use cw_bid_factory::msg::{FactoryExecuteMsg, FactoryInstantiateMsg, FactoryQueryMsg};
+
+fn main() {
+    write_api! {
+        instantiate: FactoryInstantiateMsg,
+        execute: FactoryExecuteMsg,
+        query: FactoryQueryMsg,
+    }
+}
@@ -0,0 +1,51 @@
+mod execute;
+mod instantiate;
+mod query;
+mod reply;
+
+use crate::contract::execute::_execute;
+use crate::contract::instantiate::_instantiate;
+use crate::contract::query::_query;
+use crate::contract::reply::_reply;
+
+use crate::error::FactoryError;
+use crate::msg::{FactoryExecuteMsg, FactoryInstantiateMsg, FactoryQueryMsg};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
+use cw2::set_contract_version;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw-bid-factory";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: FactoryInstantiateMsg,
+) -> Result<Response, FactoryError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    _instantiate(deps, env, info, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: FactoryExecuteMsg,
+) -> Result<Response, FactoryError> {
+    _execute(deps, env, info, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: FactoryQueryMsg) -> StdResult<Binary> {
+    _query(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, FactoryError> {
+    _reply(deps, env, msg)
+}
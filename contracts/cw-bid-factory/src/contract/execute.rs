@@ -0,0 +1,104 @@
+use crate::contract::reply::INSTANTIATE_REPLY_ID;
+use crate::error::FactoryError;
+use crate::msg::FactoryExecuteMsg;
+use crate::state::{auctions, AUCTION_COUNT, CONFIG};
+use cosmwasm_std::{to_binary, Addr, DepsMut, Env, MessageInfo, Response, SubMsg, WasmMsg};
+use cw_bid::msg::{BidInstantiateMsg, BidQueryMsg};
+use cw_utils::nonpayable;
+
+pub fn _execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: FactoryExecuteMsg,
+) -> Result<Response, FactoryError> {
+    nonpayable(&info)?;
+
+    match msg {
+        FactoryExecuteMsg::CreateAuction { config } => create_auction(deps, env, info, config),
+        FactoryExecuteMsg::SyncAuction { address } => sync_auction(deps, address),
+        FactoryExecuteMsg::UpdateConfig { owner, code_id } => {
+            update_config(deps, info, owner, code_id)
+        }
+    }
+}
+
+// the auction is registered once instantiated, in the reply
+fn create_auction(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    mut config: Box<BidInstantiateMsg>,
+) -> Result<Response, FactoryError> {
+    // sellers only list their own auctions, without an owner the factory would be the seller
+    let seller = match &config.owner {
+        Some(owner) => deps.api.addr_validate(owner)?,
+        None => info.sender.clone(),
+    };
+    if seller != info.sender {
+        return Err(FactoryError::NotSeller);
+    }
+    config.owner = Some(seller.to_string());
+
+    let factory = CONFIG.load(deps.storage)?;
+    let count = AUCTION_COUNT.load(deps.storage)? + 1;
+    AUCTION_COUNT.save(deps.storage, &count)?;
+
+    let instantiate = WasmMsg::Instantiate {
+        admin: factory.auction_admin.map(String::from),
+        code_id: factory.code_id,
+        msg: to_binary(&config)?,
+        funds: vec![],
+        label: format!("cw-bid auction {}", count),
+    };
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(instantiate, INSTANTIATE_REPLY_ID))
+        .add_attribute("seller", seller)
+        .add_attribute("method", "create_auction"))
+}
+
+// a renounced auction stays listed under its last seller
+fn sync_auction(deps: DepsMut, address: String) -> Result<Response, FactoryError> {
+    let address = deps.api.addr_validate(&address)?;
+    let auctions = auctions();
+    let mut record = auctions.load(deps.storage, address.clone())?;
+
+    let owner: Option<Addr> = deps
+        .querier
+        .query_wasm_smart(&address, &BidQueryMsg::Owner {})?;
+    if let Some(owner) = owner {
+        record.seller = owner;
+        auctions.save(deps.storage, address.clone(), &record)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("auction", address)
+        .add_attribute("seller", record.seller)
+        .add_attribute("method", "sync_auction"))
+}
+
+fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    code_id: Option<u64>,
+) -> Result<Response, FactoryError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(FactoryError::Unauthorized);
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_validate(&owner)?;
+    }
+    if let Some(code_id) = code_id {
+        config.code_id = code_id;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("owner", config.owner)
+        .add_attribute("code_id", config.code_id.to_string())
+        .add_attribute("method", "update_config"))
+}
@@ -0,0 +1,36 @@
+use crate::error::FactoryError;
+use crate::msg::{Config, FactoryInstantiateMsg};
+use crate::state::{AUCTION_COUNT, CONFIG};
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+
+pub fn _instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: FactoryInstantiateMsg,
+) -> Result<Response, FactoryError> {
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+
+    let auction_admin = msg
+        .auction_admin
+        .map(|admin| deps.api.addr_validate(&admin))
+        .transpose()?;
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            owner: owner.clone(),
+            code_id: msg.code_id,
+            auction_admin,
+        },
+    )?;
+    AUCTION_COUNT.save(deps.storage, &0)?;
+
+    Ok(Response::new()
+        .add_attribute("owner", owner)
+        .add_attribute("code_id", msg.code_id.to_string())
+        .add_attribute("method", "instantiate"))
+}
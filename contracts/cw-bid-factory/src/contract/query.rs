@@ -0,0 +1,109 @@
+use crate::msg::{AuctionInfo, AuctionsResponse, FactoryQueryMsg};
+use crate::state::{auctions, AuctionRecord, CONFIG};
+use cosmwasm_std::{to_binary, Addr, Binary, Deps, Env, Order, StdResult};
+use cw_bid::msg::{AuctionStatus, BidQueryMsg};
+use cw_storage_plus::Bound;
+use std::mem::discriminant;
+
+// pagination of the registry
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+pub fn _query(deps: Deps, _env: Env, msg: FactoryQueryMsg) -> StdResult<Binary> {
+    match msg {
+        FactoryQueryMsg::Config {} => to_binary(&CONFIG.load(deps.storage)?),
+        FactoryQueryMsg::Auction { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            let record = auctions().load(deps.storage, address.clone())?;
+            to_binary(&auction_info(deps, address, record)?)
+        }
+        FactoryQueryMsg::Auctions {
+            seller,
+            denom,
+            status,
+            start_after,
+            limit,
+        } => to_binary(&list_auctions(
+            deps,
+            seller,
+            denom,
+            status,
+            start_after,
+            limit,
+        )?),
+    }
+}
+
+// the most selective index is used, the other filters are applied on the way.
+// Every record read may cost a status query, so the limit bounds the reads.
+fn list_auctions(
+    deps: Deps,
+    seller: Option<String>,
+    denom: Option<String>,
+    status: Option<AuctionStatus>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AuctionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .map(Bound::exclusive);
+
+    let auctions = auctions();
+    let records = match (&seller, &denom) {
+        (Some(seller), _) => auctions
+            .idx
+            .seller
+            .prefix(deps.api.addr_validate(seller)?)
+            .range(deps.storage, start, None, Order::Ascending),
+        (None, Some(denom)) => auctions.idx.denom.prefix(denom.clone()).range(
+            deps.storage,
+            start,
+            None,
+            Order::Ascending,
+        ),
+        (None, None) => auctions.range(deps.storage, start, None, Order::Ascending),
+    };
+
+    let mut infos = vec![];
+    let mut read = 0;
+    let mut last = None;
+    for record in records.take(limit) {
+        let (address, record) = record?;
+        read += 1;
+        last = Some(address.clone());
+        if denom.iter().any(|denom| *denom != record.denom) {
+            continue;
+        }
+
+        let info = auction_info(deps, address, record)?;
+        if let Some(status) = &status {
+            if discriminant(status) != discriminant(&info.status) {
+                continue;
+            }
+        }
+
+        infos.push(info);
+    }
+
+    Ok(AuctionsResponse {
+        auctions: infos,
+        next: if read == limit { last } else { None },
+    })
+}
+
+fn auction_info(deps: Deps, address: Addr, record: AuctionRecord) -> StdResult<AuctionInfo> {
+    let status = deps
+        .querier
+        .query_wasm_smart(&address, &BidQueryMsg::Status {})?;
+
+    Ok(AuctionInfo {
+        address,
+        seller: record.seller,
+        denom: record.denom,
+        code_id: record.code_id,
+        created_at: record.created_at,
+        status,
+    })
+}
@@ -0,0 +1,39 @@
+use crate::error::FactoryError;
+use crate::state::{auctions, AuctionRecord, CONFIG};
+use cosmwasm_std::{Addr, DepsMut, Env, Reply, Response, StdError};
+use cw_bid::msg::BidQueryMsg;
+use cw_utils::parse_reply_instantiate_data;
+
+pub const INSTANTIATE_REPLY_ID: u64 = 1;
+
+pub fn _reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, FactoryError> {
+    match msg.id {
+        INSTANTIATE_REPLY_ID => register_auction(deps, env, msg),
+        id => Err(StdError::generic_err(format!("Unknown reply id {}", id)).into()),
+    }
+}
+
+// seller and denom are read back from the auction, which applied the defaults
+fn register_auction(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, FactoryError> {
+    let response = parse_reply_instantiate_data(msg)?;
+    let address = deps.api.addr_validate(&response.contract_address)?;
+
+    let seller: Addr = deps
+        .querier
+        .query_wasm_smart(&address, &BidQueryMsg::GetOwner {})?;
+    let denom: String = deps
+        .querier
+        .query_wasm_smart(&address, &BidQueryMsg::GetDenom {})?;
+
+    let record = AuctionRecord {
+        seller,
+        denom,
+        code_id: CONFIG.load(deps.storage)?.code_id,
+        created_at: env.block.time,
+    };
+    auctions().save(deps.storage, address.clone(), &record)?;
+
+    Ok(Response::new()
+        .add_attribute("auction", address)
+        .add_attribute("method", "register_auction"))
+}
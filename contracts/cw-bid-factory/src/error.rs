@@ -0,0 +1,21 @@
+use cosmwasm_std::StdError;
+use cw_utils::{ParseReplyError, PaymentError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FactoryError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Only the owner can update the config")]
+    Unauthorized,
+
+    #[error("Auctions can only be created for the sender")]
+    NotSeller,
+}
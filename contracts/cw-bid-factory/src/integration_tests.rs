@@ -0,0 +1,352 @@
+#[cfg(test)]
+mod integration {
+    use crate::contract::{execute, instantiate, query, reply};
+    use crate::error::FactoryError;
+    use crate::msg::{
+        AuctionInfo, AuctionsResponse, Config, FactoryExecuteMsg, FactoryInstantiateMsg,
+        FactoryQueryMsg,
+    };
+    use cosmwasm_std::{coins, Addr, Empty, StdResult};
+    use cw_bid::msg::{AuctionStatus, BidExecuteMsg, BidInstantiateMsg, BidQueryMsg};
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+    use cw_utils::PaymentError;
+
+    pub struct FactoryContract(Addr);
+
+    impl FactoryContract {
+        #[track_caller]
+        pub fn instantiate(
+            app: &mut App,
+            code_id: u64,
+            sender: &Addr,
+            bid_code_id: u64,
+            auction_admin: Option<&Addr>,
+        ) -> StdResult<FactoryContract> {
+            app.instantiate_contract(
+                code_id,
+                sender.clone(),
+                &FactoryInstantiateMsg {
+                    owner: None,
+                    code_id: bid_code_id,
+                    auction_admin: auction_admin.map(Addr::to_string),
+                },
+                &[],
+                "factory",
+                None,
+            )
+            .map(FactoryContract)
+            .map_err(|err| err.downcast().unwrap())
+        }
+
+        #[track_caller]
+        pub fn create_auction(
+            &self,
+            app: &mut App,
+            sender: &Addr,
+            config: BidInstantiateMsg,
+        ) -> Result<(), FactoryError> {
+            app.execute_contract(
+                sender.clone(),
+                self.0.clone(),
+                &FactoryExecuteMsg::CreateAuction {
+                    config: Box::new(config),
+                },
+                &[],
+            )
+            .map_err(|err| err.downcast::<FactoryError>().unwrap())?;
+            Ok(())
+        }
+
+        #[track_caller]
+        pub fn auctions(
+            &self,
+            app: &App,
+            seller: Option<&Addr>,
+            denom: Option<&str>,
+            status: Option<AuctionStatus>,
+            start_after: Option<&Addr>,
+            limit: Option<u32>,
+        ) -> StdResult<AuctionsResponse> {
+            app.wrap().query_wasm_smart(
+                self.0.clone(),
+                &FactoryQueryMsg::Auctions {
+                    seller: seller.map(Addr::to_string),
+                    denom: denom.map(String::from),
+                    status,
+                    start_after: start_after.map(Addr::to_string),
+                    limit,
+                },
+            )
+        }
+    }
+
+    fn factory_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        Box::new(contract)
+    }
+
+    fn bid_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw_bid::contract::execute,
+            cw_bid::contract::instantiate,
+            cw_bid::contract::query,
+        )
+        .with_reply(cw_bid::contract::reply);
+        Box::new(contract)
+    }
+
+    fn addresses(page: &AuctionsResponse) -> Vec<Addr> {
+        page.auctions
+            .iter()
+            .map(|info| info.address.clone())
+            .collect()
+    }
+
+    #[test]
+    fn registry() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let seller = Addr::unchecked("seller");
+        let other_seller = Addr::unchecked("other_seller");
+        let alex = Addr::unchecked("alex");
+
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alex, coins(100_000_000u128, "ujuno"))
+                .unwrap();
+        });
+
+        let bid_id = app.store_code(bid_contract());
+        let factory_id = app.store_code(factory_contract());
+        let factory = FactoryContract::instantiate(&mut app, factory_id, &owner, bid_id, None)?;
+
+        let atom = BidInstantiateMsg::default();
+        let juno = BidInstantiateMsg {
+            denom: Some("ujuno".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            factory.create_auction(&mut app, &seller, atom.clone()),
+            Ok(())
+        );
+        assert_eq!(
+            factory.create_auction(&mut app, &seller, juno.clone()),
+            Ok(())
+        );
+        assert_eq!(
+            factory.create_auction(&mut app, &other_seller, juno),
+            Ok(())
+        );
+        // nobody lists an auction under another seller
+        let for_seller = BidInstantiateMsg {
+            owner: Some(seller.to_string()),
+            ..atom
+        };
+        assert_eq!(
+            factory.create_auction(&mut app, &other_seller, for_seller.clone()),
+            Err(FactoryError::NotSeller)
+        );
+        assert_eq!(
+            factory.create_auction(&mut app, &seller, for_seller),
+            Ok(())
+        );
+        // the factory keeps no funds
+        let err = app
+            .execute_contract(
+                alex.clone(),
+                factory.0.clone(),
+                &FactoryExecuteMsg::CreateAuction {
+                    config: Box::new(BidInstantiateMsg::default()),
+                },
+                &coins(1_000, "ujuno"),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<FactoryError>().unwrap(),
+            FactoryError::Payment(PaymentError::NonPayable {})
+        );
+
+        let all = factory.auctions(&app, None, None, None, None, None)?;
+        assert_eq!(all.auctions.len(), 4);
+        assert_eq!(all.next, None);
+        let [first, second, third, fourth] = [0, 1, 2, 3].map(|i| all.auctions[i].address.clone());
+
+        // the sender owns its auction, nobody can migrate it without an auction admin
+        let auction_owner: Addr = app
+            .wrap()
+            .query_wasm_smart(first.clone(), &BidQueryMsg::GetOwner {})?;
+        assert_eq!(auction_owner, seller);
+        assert_eq!(
+            app.wrap().query_wasm_contract_info(first.clone())?.admin,
+            None
+        );
+
+        assert_eq!(
+            addresses(&factory.auctions(&app, Some(&seller), None, None, None, None)?),
+            vec![first.clone(), second.clone(), fourth.clone()]
+        );
+        assert_eq!(
+            addresses(&factory.auctions(&app, None, Some("ujuno"), None, None, None)?),
+            vec![second.clone(), third.clone()]
+        );
+        assert_eq!(
+            addresses(&factory.auctions(&app, Some(&seller), Some("ujuno"), None, None, None)?),
+            vec![second.clone()]
+        );
+
+        // pagination
+        let page = factory.auctions(&app, None, None, None, None, Some(2))?;
+        assert_eq!(addresses(&page), vec![first.clone(), second.clone()]);
+        assert_eq!(page.next, Some(second.clone()));
+        let page = factory.auctions(&app, None, None, None, Some(&second), Some(2))?;
+        assert_eq!(addresses(&page), vec![third.clone(), fourth.clone()]);
+        let page = factory.auctions(&app, Some(&seller), None, None, Some(&first), Some(1))?;
+        assert_eq!(addresses(&page), vec![second.clone()]);
+
+        // the status is the live one of the auction
+        app.execute_contract(
+            alex.clone(),
+            second.clone(),
+            &BidExecuteMsg::Bid {
+                referrer: None,
+                proof: None,
+                ibc_origin: None,
+                expires_at: None,
+                on_behalf_of: None,
+            },
+            &coins(10_000_000, "ujuno"),
+        )
+        .unwrap();
+        app.execute_contract(
+            seller.clone(),
+            second.clone(),
+            &BidExecuteMsg::Close {},
+            &[],
+        )
+        .unwrap();
+
+        let closed = factory.auctions(&app, None, None, Some(AuctionStatus::Closed), None, None)?;
+        assert_eq!(addresses(&closed), vec![second.clone()]);
+        let open = factory.auctions(
+            &app,
+            Some(&seller),
+            None,
+            Some(AuctionStatus::Open),
+            None,
+            None,
+        )?;
+        assert_eq!(addresses(&open), vec![first.clone(), fourth.clone()]);
+
+        // a status filter reads at most `limit` records per page
+        let page =
+            factory.auctions(&app, None, None, Some(AuctionStatus::Closed), None, Some(2))?;
+        assert_eq!(addresses(&page), vec![second.clone()]);
+        assert_eq!(page.next, Some(second.clone()));
+        let page = factory.auctions(
+            &app,
+            None,
+            None,
+            Some(AuctionStatus::Closed),
+            page.next.as_ref(),
+            Some(2),
+        )?;
+        assert_eq!(addresses(&page), Vec::<Addr>::new());
+        assert_eq!(page.next, Some(fourth.clone()));
+
+        // the seller index follows an ownership transfer once synced
+        for (sender, msg) in [
+            (
+                &seller,
+                BidExecuteMsg::ProposeNewOwner {
+                    new_owner: other_seller.to_string(),
+                    expiry: None,
+                },
+            ),
+            (&other_seller, BidExecuteMsg::AcceptOwnership {}),
+        ] {
+            app.execute_contract(sender.clone(), first.clone(), &msg, &[])
+                .unwrap();
+        }
+        app.execute_contract(
+            alex,
+            factory.0.clone(),
+            &FactoryExecuteMsg::SyncAuction {
+                address: first.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            addresses(&factory.auctions(&app, Some(&other_seller), None, None, None, None)?),
+            vec![first, third]
+        );
+
+        let info: AuctionInfo = app.wrap().query_wasm_smart(
+            factory.0.clone(),
+            &FactoryQueryMsg::Auction {
+                address: second.to_string(),
+            },
+        )?;
+        assert_eq!(info.seller, seller);
+        assert_eq!(info.denom, "ujuno");
+        assert_eq!(info.code_id, bid_id);
+        assert_eq!(info.status, AuctionStatus::Closed);
+
+        Ok(())
+    }
+
+    #[test]
+    fn update_config() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let new_owner = Addr::unchecked("new_owner");
+
+        let mut app = App::default();
+        let bid_id = app.store_code(bid_contract());
+        let new_bid_id = app.store_code(bid_contract());
+        let factory_id = app.store_code(factory_contract());
+        let admin = Addr::unchecked("admin");
+        let factory =
+            FactoryContract::instantiate(&mut app, factory_id, &owner, bid_id, Some(&admin))?;
+
+        let update = FactoryExecuteMsg::UpdateConfig {
+            owner: Some(new_owner.to_string()),
+            code_id: Some(new_bid_id),
+        };
+        let err = app
+            .execute_contract(new_owner.clone(), factory.0.clone(), &update, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<FactoryError>().unwrap(),
+            FactoryError::Unauthorized
+        );
+
+        app.execute_contract(owner, factory.0.clone(), &update, &[])
+            .unwrap();
+        let config: Config = app
+            .wrap()
+            .query_wasm_smart(factory.0.clone(), &FactoryQueryMsg::Config {})?;
+        assert_eq!(
+            config,
+            Config {
+                owner: new_owner.clone(),
+                code_id: new_bid_id,
+                auction_admin: Some(admin.clone()),
+            }
+        );
+
+        assert_eq!(
+            factory.create_auction(&mut app, &new_owner, BidInstantiateMsg::default()),
+            Ok(())
+        );
+        let auctions = factory.auctions(&app, None, None, None, None, None)?;
+        assert_eq!(auctions.auctions[0].code_id, new_bid_id);
+        assert_eq!(
+            app.wrap()
+                .query_wasm_contract_info(auctions.auctions[0].address.clone())?
+                .admin,
+            Some(admin.to_string())
+        );
+
+        Ok(())
+    }
+}
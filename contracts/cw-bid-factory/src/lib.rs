@@ -0,0 +1,5 @@
+pub mod contract;
+pub mod error;
+mod integration_tests;
+pub mod msg;
+mod state;
@@ -0,0 +1,76 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Timestamp};
+use cw_bid::msg::{AuctionStatus, BidInstantiateMsg};
+
+#[cw_serde]
+pub struct FactoryInstantiateMsg {
+    // defaults to the sender
+    pub owner: Option<String>,
+    // code id of cw-bid
+    pub code_id: u64,
+    // wasm admin of every auction, none leaves them immutable. Never the seller,
+    // who could migrate away with the escrow, and fixed once instantiated so
+    // bidders know who can migrate the auctions they bid in
+    pub auction_admin: Option<String>,
+}
+
+#[cw_serde]
+pub enum FactoryExecuteMsg {
+    // the auction is owned by the sender, `config.owner` can only be the sender
+    CreateAuction {
+        config: Box<BidInstantiateMsg>,
+    },
+    // reads the owner of the auction again after an ownership transfer, anyone can call it
+    SyncAuction {
+        address: String,
+    },
+    // auctions already created keep their code id
+    UpdateConfig {
+        owner: Option<String>,
+        code_id: Option<u64>,
+    },
+}
+
+#[cw_serde]
+pub struct Config {
+    pub owner: Addr,
+    pub code_id: u64,
+    pub auction_admin: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct AuctionInfo {
+    pub address: Addr,
+    pub seller: Addr,
+    pub denom: String,
+    pub code_id: u64,
+    pub created_at: Timestamp,
+    // queried from the auction
+    pub status: AuctionStatus,
+}
+
+#[cw_serde]
+pub struct AuctionsResponse {
+    pub auctions: Vec<AuctionInfo>,
+    // start_after of the next page, none once every record was read
+    pub next: Option<Addr>,
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum FactoryQueryMsg {
+    #[returns(Config)]
+    Config {},
+    #[returns(AuctionInfo)]
+    Auction { address: String },
+    // filters are combined, `status` matches on the variant only.
+    // At most `limit` records are read, filtered out ones included.
+    #[returns(AuctionsResponse)]
+    Auctions {
+        seller: Option<String>,
+        denom: Option<String>,
+        status: Option<AuctionStatus>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
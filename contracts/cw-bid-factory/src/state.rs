@@ -0,0 +1,45 @@
+use crate::msg::Config;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Timestamp};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
+
+pub const CONFIG: Item<Config> = Item::new("CONFIG");
+// numbers the labels of the auctions
+pub const AUCTION_COUNT: Item<u64> = Item::new("AUCTION_COUNT");
+
+#[cw_serde]
+pub struct AuctionRecord {
+    pub seller: Addr,
+    pub denom: String,
+    pub code_id: u64,
+    pub created_at: Timestamp,
+}
+
+pub struct AuctionIndexes<'a> {
+    pub seller: MultiIndex<'a, Addr, AuctionRecord, Addr>,
+    pub denom: MultiIndex<'a, String, AuctionRecord, Addr>,
+}
+
+impl<'a> IndexList<AuctionRecord> for AuctionIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<AuctionRecord>> + '_> {
+        let v: Vec<&dyn Index<AuctionRecord>> = vec![&self.seller, &self.denom];
+        Box::new(v.into_iter())
+    }
+}
+
+// every auction created by the factory, keyed by its address
+pub fn auctions<'a>() -> IndexedMap<'a, Addr, AuctionRecord, AuctionIndexes<'a>> {
+    let indexes = AuctionIndexes {
+        seller: MultiIndex::new(
+            |_pk, record| record.seller.clone(),
+            "AUCTIONS",
+            "AUCTIONS__SELLER",
+        ),
+        denom: MultiIndex::new(
+            |_pk, record| record.denom.clone(),
+            "AUCTIONS",
+            "AUCTIONS__DENOM",
+        ),
+    };
+    IndexedMap::new("AUCTIONS", indexes)
+}
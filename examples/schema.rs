@@ -1,5 +1,5 @@
 use cosmwasm_schema::write_api;
-use cw_bid::msg::{BidExecuteMsg, BidInstantiateMsg, BidMigrateMsg, BidQueryMsg};
+use cw_bid::msg::{BidExecuteMsg, BidInstantiateMsg, BidMigrateMsg, BidQueryMsg, BidSudoMsg};
 
 fn main() {
     write_api! {
@@ -7,5 +7,6 @@ fn main() {
         execute: BidExecuteMsg,
         query: BidQueryMsg,
         migrate: BidMigrateMsg,
+        sudo: BidSudoMsg,
     }
 }
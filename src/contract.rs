@@ -1,16 +1,33 @@
+mod deposit;
 mod execute;
+mod ibc;
 mod instantiate;
+mod migrate;
 mod query;
+mod reply;
+mod staking;
+mod sudo;
 
 use crate::contract::execute::_execute;
+use crate::contract::ibc::{
+    _ibc_channel_close, _ibc_channel_connect, _ibc_channel_open, _ibc_packet_ack,
+    _ibc_packet_receive, _ibc_packet_timeout,
+};
 use crate::contract::instantiate::_instantiate;
+use crate::contract::migrate::_migrate;
 use crate::contract::query::_query;
+use crate::contract::reply::_reply;
+use crate::contract::sudo::_sudo;
 
 use crate::error::BidError;
-use crate::msg::{BidExecuteMsg, BidInstantiateMsg, BidMigrateMsg, BidQueryMsg};
+use crate::msg::{BidExecuteMsg, BidInstantiateMsg, BidMigrateMsg, BidQueryMsg, BidSudoMsg};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    Binary, Deps, DepsMut, Env, IbcBasicResponse, IbcChannelCloseMsg, IbcChannelConnectMsg,
+    IbcChannelOpenMsg, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg,
+    IbcReceiveResponse, MessageInfo, Reply, Response, StdResult,
+};
 use cw2::set_contract_version;
 
 // version info for migration info
@@ -44,6 +61,66 @@ pub fn query(deps: Deps, env: Env, msg: BidQueryMsg) -> StdResult<Binary> {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: BidMigrateMsg) -> Result<Response, BidError> {
-    Ok(Response::default())
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, BidError> {
+    _reply(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: BidMigrateMsg) -> Result<Response, BidError> {
+    _migrate(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: BidSudoMsg) -> Result<Response, BidError> {
+    _sudo(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(deps: DepsMut, _env: Env, msg: IbcChannelOpenMsg) -> Result<(), BidError> {
+    _ibc_channel_open(deps, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, BidError> {
+    _ibc_channel_connect(deps, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, BidError> {
+    _ibc_channel_close(deps, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, BidError> {
+    _ibc_packet_receive(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, BidError> {
+    _ibc_packet_ack(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, BidError> {
+    _ibc_packet_timeout(deps, env, msg)
 }
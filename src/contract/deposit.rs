@@ -0,0 +1,187 @@
+use crate::contract::execute::bid;
+use crate::error::BidError;
+use crate::msg::BidAllowance;
+use crate::state::{ACCEPTED_ASSETS, ALLOWANCES, DENOM, DEPOSITS, TOTAL_DEPOSITS};
+use cosmwasm_std::{
+    Addr, BankMsg, BlockInfo, Coin, DepsMut, Env, MessageInfo, Response, StdResult, Storage,
+    Uint128,
+};
+use cw_utils::Expiration;
+
+pub fn deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, BidError> {
+    if info.funds.iter().all(|coin| coin.amount.is_zero()) {
+        return Err(BidError::NothingDeposited);
+    }
+
+    let quote = DENOM.load(deps.storage)?;
+    for coin in &info.funds {
+        if coin.denom != quote && !ACCEPTED_ASSETS.has(deps.storage, coin.denom.clone()) {
+            return Err(BidError::UnacceptedDenom {
+                denom: coin.denom.clone(),
+            });
+        }
+
+        credit_deposit(deps.storage, &info.sender, coin)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("owner", info.sender)
+        .add_attribute("method", "deposit"))
+}
+
+pub fn credit_deposit(storage: &mut dyn Storage, owner: &Addr, coin: &Coin) -> StdResult<()> {
+    let key = (owner.clone(), coin.denom.clone());
+    let balance = DEPOSITS.may_load(storage, key.clone())?;
+    DEPOSITS.save(storage, key, &(balance.unwrap_or_default() + coin.amount))?;
+    TOTAL_DEPOSITS.update(storage, coin.denom.clone(), |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default() + coin.amount)
+    })?;
+
+    Ok(())
+}
+
+pub fn withdraw_deposit(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Coin,
+) -> Result<Response, BidError> {
+    take_deposit(deps.storage, &info.sender, &amount)?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![amount],
+        })
+        .add_attribute("owner", info.sender)
+        .add_attribute("method", "withdraw_deposit"))
+}
+
+pub fn grant_bid_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    agent: String,
+    max: Coin,
+    expires: Option<Expiration>,
+) -> Result<Response, BidError> {
+    let agent = deps.api.addr_validate(&agent)?;
+    if agent == info.sender {
+        return Err(BidError::InvalidAllowance {
+            reason: "cannot grant an allowance to yourself".to_string(),
+        });
+    }
+
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(BidError::InvalidAllowance {
+            reason: "already expired".to_string(),
+        });
+    }
+
+    ALLOWANCES.save(
+        deps.storage,
+        (info.sender.clone(), agent.clone()),
+        &BidAllowance {
+            remaining: max,
+            expires,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("owner", info.sender)
+        .add_attribute("agent", agent)
+        .add_attribute("method", "grant_bid_allowance"))
+}
+
+pub fn revoke_allowance(
+    deps: DepsMut,
+    info: MessageInfo,
+    agent: String,
+) -> Result<Response, BidError> {
+    let key = (info.sender.clone(), deps.api.addr_validate(&agent)?);
+    if !ALLOWANCES.has(deps.storage, key.clone()) {
+        return Err(BidError::NoAllowance);
+    }
+    ALLOWANCES.remove(deps.storage, key);
+
+    Ok(Response::new()
+        .add_attribute("owner", info.sender)
+        .add_attribute("agent", agent)
+        .add_attribute("method", "revoke_allowance"))
+}
+
+// the bid is placed as if the owner had sent the funds itself
+pub fn bid_from_deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    amount: Coin,
+    proof: Option<Vec<String>>,
+    expires_at: Option<Expiration>,
+) -> Result<Response, BidError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    if info.sender != owner {
+        spend_allowance(deps.storage, &env.block, &owner, &info.sender, &amount)?;
+    }
+    take_deposit(deps.storage, &owner, &amount)?;
+
+    let owner_info = MessageInfo {
+        sender: owner,
+        funds: vec![amount],
+    };
+    let response = bid(deps, env, owner_info, None, proof, None, expires_at, None)?;
+
+    Ok(response.add_attribute("agent", info.sender))
+}
+
+fn spend_allowance(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    owner: &Addr,
+    agent: &Addr,
+    amount: &Coin,
+) -> Result<(), BidError> {
+    let key = (owner.clone(), agent.clone());
+    let mut allowance = ALLOWANCES
+        .may_load(storage, key.clone())?
+        .ok_or(BidError::NoAllowance)?;
+
+    if allowance.expires.is_expired(block) {
+        return Err(BidError::AllowanceExpired);
+    }
+
+    if allowance.remaining.denom != amount.denom || allowance.remaining.amount < amount.amount {
+        return Err(BidError::AllowanceExceeded);
+    }
+    allowance.remaining.amount -= amount.amount;
+    ALLOWANCES.save(storage, key, &allowance)?;
+
+    Ok(())
+}
+
+fn take_deposit(storage: &mut dyn Storage, owner: &Addr, amount: &Coin) -> Result<(), BidError> {
+    let key = (owner.clone(), amount.denom.clone());
+    let balance = DEPOSITS.may_load(storage, key.clone())?.unwrap_or_default();
+    if balance < amount.amount {
+        return Err(BidError::InsufficientDeposit);
+    }
+
+    if balance == amount.amount {
+        DEPOSITS.remove(storage, key);
+    } else {
+        DEPOSITS.save(storage, key, &(balance - amount.amount))?;
+    }
+    TOTAL_DEPOSITS.update(storage, amount.denom.clone(), |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default() - amount.amount)
+    })?;
+
+    Ok(())
+}
+
+// still held for depositors, not part of any bid
+pub fn total_deposits(storage: &dyn Storage, denom: &str) -> StdResult<Uint128> {
+    Ok(TOTAL_DEPOSITS
+        .may_load(storage, denom.to_string())?
+        .unwrap_or_default())
+}
@@ -1,53 +1,348 @@
+use crate::contract::deposit::{
+    bid_from_deposit, deposit, grant_bid_allowance, revoke_allowance, withdraw_deposit,
+};
+use crate::contract::instantiate::{validate_merkle_root, validate_metadata};
+use crate::contract::reply::{HOOK_REPLY_ID, IBC_REFUND_REPLY_ID, WINNER_CALLBACK_REPLY_ID};
+use crate::contract::staking::{
+    add_escrow, delegate_msgs, escrow, owed_escrows, release_stake, remove_escrow, stake_locked,
+    take_released, take_reward_share, unstake, unstake_msgs, Escrow,
+};
 use crate::error::BidError;
-use crate::msg::BidExecuteMsg;
+use crate::events::{AuctionClosed, BidPlaced, BidRetracted};
+use crate::gate::GateQueryMsg;
+use crate::hooks::{AuctionHook, AuctionHookMsg, WinnerCallbackMsg};
+use crate::ibc_hooks::{self, transfer_with_callback};
+use crate::merkle;
+use crate::msg::{
+    AuctionMetadata, AuctionStatus, BidExecuteMsg, CommissionPolicy, FeeRecipient, FeeShare,
+    IbcOrigin, PendingOwnership, Role,
+};
+use crate::oracle::{OracleQueryMsg, PriceResponse};
+use crate::packet::AuctionPacket;
+use crate::royalties::{Cw2981QueryMsg, Cw721QueryMsg, RoyaltiesInfoResponse};
 use crate::state::{
-    BID_BY_ADDR, BID_CLOSED, BID_RETRACTED_FOR_ADDR, BID_WINNER, COMMISSION_BY_ADDR, DENOM,
-    HIGHEST_BIDDER, OWNER,
+    auction_status, bid_denom, bid_valid, highest_bidder, refund_address, refund_shares,
+    role_members, IbcRefund, ACCEPTED_ASSETS, ADMINS, ALLOWLIST, ALLOWLIST_ONLY, APPROVED_FUNDERS,
+    AUCTION_ITEM, AWAITING_CONTROLLER, BID_BY_ADDR, BID_CLOSED, BID_DENOM_BY_ADDR, BID_EXPIRES_AT,
+    BID_RETRACTED_FOR_ADDR, BID_WINNER, COMMISSION, COMMISSION_BY_ADDR, COMMISSION_POLICY,
+    COMMISSION_SPLIT, DENOM, DENYLIST, EXPIRATION, FROZEN, FUNDED_BY_ADDR, FUNDER_BY_ADDR, GATE,
+    HIGHEST_BIDDER, HOOKS, IBC_ORIGIN_BY_ADDR, LEADERS, MERKLE_ROOT, METADATA, NOTIFY_WINNER,
+    OPERATORS, ORACLE, OWNER, PAUSED_AT, PAY_ROYALTIES, PENDING_IBC_REFUND, PENDING_OWNER,
+    PROCEEDS_SPLIT, REFERRER_BY_ADDR, REMOTE_BIDDER_BY_ADDR, REMOTE_PROCEEDS_PENDING, REWARDS,
 };
 use cosmwasm_std::{
-    has_coins, BankMsg, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response, Uint128,
+    coins, has_coins, to_binary, Addr, BankMsg, BlockInfo, Coin, CosmosMsg, Deps, DepsMut, Env,
+    IbcMsg, IbcTimeout, MessageInfo, Order, Response, StdError, StdResult, Storage, SubMsg,
+    Uint128, WasmMsg,
 };
+use cw_storage_plus::Map;
+use cw_utils::Expiration;
 use std::ops::{AddAssign, SubAssign};
 
-// constant cut of 0.5 token by bid
-const CONTRACT_COMMISSION: u128 = 500_000u128;
+// packets and refunds sent over ibc time out after a day, a timed out
+// ics-20 refund returns to the contract
+pub const IBC_TIMEOUT: u64 = 86_400;
+
+// gas a subscriber may spend on a single event
+const HOOK_GAS_LIMIT: u64 = 300_000;
 
 pub fn _execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: BidExecuteMsg,
 ) -> Result<Response, BidError> {
+    // only governance can unfreeze through sudo
+    if FROZEN.load(deps.storage)? {
+        return Err(BidError::Frozen);
+    }
+
     match msg {
-        BidExecuteMsg::Bid {} => bid(deps, info),
-        BidExecuteMsg::Close {} => close(deps, info),
-        BidExecuteMsg::Retract { receiver } => retract(deps, info, receiver),
+        BidExecuteMsg::Bid {
+            referrer,
+            proof,
+            ibc_origin,
+            expires_at,
+            on_behalf_of,
+        } => bid(
+            deps,
+            env,
+            info,
+            referrer,
+            proof,
+            ibc_origin,
+            expires_at,
+            on_behalf_of,
+        ),
+        BidExecuteMsg::Close {} => close(deps, env, info),
+        BidExecuteMsg::Retract { receiver } => retract(deps, env, info, receiver),
+        BidExecuteMsg::ProposeNewOwner { new_owner, expiry } => {
+            propose_new_owner(deps, env, info, new_owner, expiry)
+        }
+        BidExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        BidExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
+        BidExecuteMsg::RenounceOwnership {} => renounce_ownership(deps, info),
+        BidExecuteMsg::AddRole { address, role } => add_role(deps, info, address, role),
+        BidExecuteMsg::RemoveRole { address, role } => remove_role(deps, info, address, role),
+        BidExecuteMsg::SetExpiration { expiration } => set_expiration(deps, env, info, expiration),
+        BidExecuteMsg::Cancel {} => cancel(deps, env, info),
+        BidExecuteMsg::SetAllowlistOnly { enabled } => set_allowlist_only(deps, info, enabled),
+        BidExecuteMsg::UpdateAllowlist { add, remove } => {
+            update_address_list(deps, info, ALLOWLIST, add, remove, "update_allowlist")
+        }
+        BidExecuteMsg::UpdateDenylist { add, remove } => {
+            update_address_list(deps, info, DENYLIST, add, remove, "update_denylist")
+        }
+        BidExecuteMsg::SetMerkleRoot { root } => set_merkle_root(deps, info, root),
+        BidExecuteMsg::SetGate { address } => set_gate(deps, info, address),
+        BidExecuteMsg::Pause {} => pause(deps, env, info),
+        BidExecuteMsg::Unpause {} => unpause(deps, env, info),
+        BidExecuteMsg::AddHook { address } => add_hook(deps, info, address),
+        BidExecuteMsg::RemoveHook { address } => remove_hook(deps, info, address),
+        BidExecuteMsg::UpdateMetadata { metadata } => update_metadata(deps, info, metadata),
+        BidExecuteMsg::Unstake {} => unstake(deps, env),
+        BidExecuteMsg::ReleaseStake {} => release_stake(deps, env),
+        BidExecuteMsg::CollectProceeds {} => collect_proceeds(deps, env),
+        BidExecuteMsg::Deposit {} => deposit(deps, info),
+        BidExecuteMsg::WithdrawDeposit { amount } => withdraw_deposit(deps, info, amount),
+        BidExecuteMsg::GrantBidAllowance {
+            agent,
+            max,
+            expires,
+        } => grant_bid_allowance(deps, env, info, agent, max, expires),
+        BidExecuteMsg::RevokeAllowance { agent } => revoke_allowance(deps, info, agent),
+        BidExecuteMsg::BidFromDeposit {
+            owner,
+            amount,
+            proof,
+            expires_at,
+        } => bid_from_deposit(deps, env, info, owner, amount, proof, expires_at),
+        BidExecuteMsg::ApproveFunder { funder } => approve_funder(deps, info, funder),
+        BidExecuteMsg::RevokeFunder { funder } => revoke_funder(deps, info, funder),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    referrer: Option<String>,
+    proof: Option<Vec<String>>,
+    ibc_origin: Option<IbcOrigin>,
+    expires_at: Option<Expiration>,
+    on_behalf_of: Option<String>,
+) -> Result<Response, BidError> {
+    let bidder = match on_behalf_of {
+        Some(bidder) => deps.api.addr_validate(&bidder)?,
+        None => info.sender.clone(),
+    };
+
+    let expires_at = expires_at.filter(|expires_at| *expires_at != Expiration::Never {});
+    let checked = check_bid(
+        deps.as_ref(),
+        &env,
+        &bidder,
+        &info.funds,
+        referrer,
+        proof,
+        expires_at.as_ref(),
+    )?;
+
+    let funder = info.sender;
+    ensure_funder(deps.as_ref(), &bidder, &funder, checked.current_bid)?;
+
+    // a cross chain bid must come from the ibc-hooks account of its origin
+    if let Some(origin) = &ibc_origin {
+        let intermediate =
+            ibc_hooks::intermediate_sender(deps.api, &origin.channel, &origin.sender)?;
+        if intermediate != funder {
+            return Err(BidError::InvalidIbcOrigin);
+        }
+    }
+
+    // the lead found by the check, with the invalid bids on top dropped
+    let highest_bidder = update_highest_bidder(deps.storage, &env.block)?;
+
+    let mut commission = COMMISSION_BY_ADDR
+        .load(deps.storage, bidder.clone())
+        .unwrap_or_default();
+
+    if let Some(comission) = &mut commission {
+        comission.add_assign(checked.commission);
+    } else {
+        commission = Some(checked.commission);
+    }
+
+    // the first referrer given by a bidder is the one kept
+    if let Some(referrer) = checked.referrer {
+        if !REFERRER_BY_ADDR.has(deps.storage, bidder.clone()) {
+            REFERRER_BY_ADDR.save(deps.storage, bidder.clone(), &referrer)?;
+        }
+    }
+
+    // refundable commission stays in the contract until close or retract
+    let held = match COMMISSION_POLICY.load(deps.storage)? {
+        CommissionPolicy::Kept => Uint128::zero(),
+        CommissionPolicy::Refundable => checked.commission,
+    };
+    let commission_msgs = match COMMISSION_POLICY.load(deps.storage)? {
+        CommissionPolicy::Kept => split_payment(
+            deps.as_ref(),
+            &checked.denom,
+            checked.commission,
+            &COMMISSION_SPLIT.load(deps.storage)?,
+            REFERRER_BY_ADDR.may_load(deps.storage, bidder.clone())?,
+        )?,
+        CommissionPolicy::Refundable => vec![],
+    };
+
+    if let Some(origin) = ibc_origin {
+        IBC_ORIGIN_BY_ADDR.save(deps.storage, bidder.clone(), &origin)?;
+    }
+
+    if checked.denom != DENOM.load(deps.storage)? {
+        BID_DENOM_BY_ADDR.save(deps.storage, bidder.clone(), &checked.denom)?;
     }
+
+    let event = BidPlaced {
+        auction_id: env.contract.address.to_string(),
+        bidder: bidder.to_string(),
+        denom: checked.denom.clone(),
+        increment: checked.new_bid - checked.current_bid,
+        new_total: checked.new_bid,
+        previous_leader: highest_bidder.as_ref().map(Addr::to_string),
+        commission: checked.commission,
+        funder: Some(funder.to_string()).filter(|_| funder != bidder),
+    };
+
+    let mut hooks = vec![AuctionHook::BidPlaced {
+        bidder: bidder.to_string(),
+        amount: checked.new_bid,
+    }];
+    if let Some(previous) = highest_bidder.filter(|previous| *previous != bidder) {
+        hooks.push(AuctionHook::Outbid {
+            bidder: previous.to_string(),
+            amount: checked.highest_bid,
+        });
+    }
+
+    match expires_at {
+        Some(expires_at) => BID_EXPIRES_AT.save(deps.storage, bidder.clone(), &expires_at)?,
+        None => BID_EXPIRES_AT.remove(deps.storage, bidder.clone()),
+    }
+
+    COMMISSION_BY_ADDR.save(deps.storage, bidder.clone(), &commission)?;
+    if funder != bidder {
+        FUNDER_BY_ADDR.save(deps.storage, bidder.clone(), &funder)?;
+        FUNDED_BY_ADDR.update(deps.storage, bidder.clone(), |funded| -> StdResult<_> {
+            Ok(funded.unwrap_or_default() + event.increment)
+        })?;
+    }
+
+    add_escrow(
+        deps.storage,
+        &Escrow {
+            bidder: bidder.clone(),
+            denom: checked.denom.clone(),
+            bid: event.increment,
+            commission: held,
+        },
+    )?;
+    LEADERS.push_back(deps.storage, &bidder)?;
+    HIGHEST_BIDDER.save(deps.storage, &Some(bidder.clone()))?;
+    BID_BY_ADDR.save(deps.storage, bidder, &checked.new_bid)?;
+
+    // only the quote denom can be staked
+    let delegate_msgs = if checked.denom == DENOM.load(deps.storage)? {
+        delegate_msgs(deps.storage, event.increment)?
+    } else {
+        vec![]
+    };
+
+    Ok(Response::new()
+        .add_messages(commission_msgs)
+        .add_messages(delegate_msgs)
+        .add_submessages(hook_msgs(deps.as_ref(), hooks)?)
+        .add_event(event.to_event())
+        .add_attribute("commission", checked.commission.to_string())
+        .add_attribute("method", "bid"))
 }
 
-fn bid(deps: DepsMut, info: MessageInfo) -> Result<Response, BidError> {
+// a bid that passed every check, amounts are in its denom
+pub struct CheckedBid {
+    pub denom: String,
+    // taken from the funds of this bid
+    pub commission: Uint128,
+    pub current_bid: Uint128,
+    pub new_bid: Uint128,
+    // of the new bid in the quote denom
+    pub value: Uint128,
+    pub highest_bidder: Option<Addr>,
+    pub highest_bid: Uint128,
+    pub referrer: Option<Addr>,
+}
+
+// every check of a bid without writing state, shared with the simulation query
+pub fn check_bid(
+    deps: Deps,
+    env: &Env,
+    bidder: &Addr,
+    funds: &[Coin],
+    referrer: Option<String>,
+    proof: Option<Vec<String>>,
+    expires_at: Option<&Expiration>,
+) -> Result<CheckedBid, BidError> {
     if BID_CLOSED.load(deps.storage)? {
         return Err(BidError::BidClosed);
     }
 
-    if info.sender == OWNER.load(deps.storage)? {
+    if let Some(expires_at) = expires_at {
+        if expires_at.is_expired(&env.block) {
+            return Err(BidError::BidExpired);
+        }
+    }
+
+    match auction_status(deps.storage, &env.block)? {
+        AuctionStatus::Expired => return Err(BidError::AuctionExpired),
+        AuctionStatus::Paused { .. } => return Err(BidError::Paused),
+        AuctionStatus::Cancelled => return Err(BidError::AuctionCancelled),
+        AuctionStatus::Frozen => return Err(BidError::Frozen),
+        AuctionStatus::Open | AuctionStatus::Closed => {}
+    }
+
+    if *bidder == OWNER.load(deps.storage)? {
         return Err(BidError::OwnerCannotBid);
     }
 
+    // bidders who pulled out during a pause are out of the auction
+    if BID_RETRACTED_FOR_ADDR.has(deps.storage, bidder.clone()) {
+        return Err(BidError::RetractAlreadyDone);
+    }
+
+    ensure_allowed(deps, bidder, proof)?;
+
+    // a bid is placed in a single accepted denom, the one of the bidder's first bid
+    let accepted_denom = funds_denom(deps, funds)?;
+    if BID_BY_ADDR.has(deps.storage, bidder.clone())
+        && bid_denom(deps.storage, bidder)? != accepted_denom
+    {
+        return Err(BidError::DenomMismatch);
+    }
+
     // Check if there is enough coin on for comission plus at least
-    let accepted_denom = DENOM.load(deps.storage)?;
+    let bid_commission = commission_for(deps.storage, &accepted_denom)?;
     if !has_coins(
-        &info.funds,
+        funds,
         &Coin {
             denom: accepted_denom.clone(),
-            amount: Uint128::from(CONTRACT_COMMISSION),
+            amount: bid_commission,
         },
     ) {
         return Err(BidError::NotEnoughCoinForCommission);
     }
 
-    // Get from state the accepted denom.
-    let highest_bidder = HIGHEST_BIDDER.load(deps.storage)?;
+    let highest_bidder = highest_bidder(deps.storage, &env.block)?;
 
     let get_bid = |addr| -> Uint128 {
         if let Ok(bid) = BID_BY_ADDR.load(deps.storage, addr) {
@@ -57,129 +352,1063 @@ fn bid(deps: DepsMut, info: MessageInfo) -> Result<Response, BidError> {
         }
     };
 
-    // get last bid.
-    let highest_bid = if let Some(bidder) = highest_bidder {
-        get_bid(bidder)
+    // get last bid, bids are compared by their value in the quote denom
+    let (highest_bid, highest_value) = if let Some(bidder) = highest_bidder.clone() {
+        let denom = bid_denom(deps.storage, &bidder)?;
+        let bid = get_bid(bidder);
+        (bid, bid_value(deps, &denom, bid)?)
     } else {
-        Uint128::from(0u128)
+        (Uint128::zero(), Uint128::zero())
     };
-    let current_bid = get_bid(info.sender.clone());
+    let current_bid = get_bid(bidder.clone());
 
     // get the amount of token to send
-    let mut new_bid = info
-        .funds
+    let mut new_bid = funds
         .iter()
         .filter(|c| c.denom == accepted_denom)
         .map(|m| m.amount)
         .sum::<Uint128>();
-    new_bid.sub_assign(Uint128::from(CONTRACT_COMMISSION));
+    new_bid.sub_assign(bid_commission);
     new_bid.add_assign(current_bid);
 
-    let mut commission = COMMISSION_BY_ADDR
-        .load(deps.storage, info.sender.clone())
-        .unwrap_or_default();
-
-    if let Some(comission) = &mut commission {
-        comission.add_assign(Uint128::from(CONTRACT_COMMISSION));
-    } else {
-        commission = Some(Uint128::from(CONTRACT_COMMISSION));
-    }
-
-    if new_bid <= highest_bid {
+    let value = bid_value(deps, &accepted_denom, new_bid)?;
+    if value <= highest_value {
         return Err(BidError::BidTooLow);
     }
 
-    let commission_msg = CosmosMsg::Bank(BankMsg::Send {
-        to_address: OWNER.load(deps.storage)?.to_string(),
-        amount: vec![Coin {
-            denom: accepted_denom,
-            amount: Uint128::from(CONTRACT_COMMISSION),
-        }],
-    });
-
-    COMMISSION_BY_ADDR.save(deps.storage, info.sender.clone(), &commission)?;
-    HIGHEST_BIDDER.save(deps.storage, &Some(info.sender.clone()))?;
-    BID_BY_ADDR.save(deps.storage, info.sender, &new_bid)?;
+    let referrer = referrer
+        .map(|referrer| deps.api.addr_validate(&referrer))
+        .transpose()?;
+    if referrer.as_ref() == Some(bidder) {
+        return Err(BidError::SelfReferral);
+    }
 
-    Ok(Response::new()
-        .add_message(commission_msg)
-        .add_attribute("sent amount", new_bid.to_string())
-        .add_attribute("commission", CONTRACT_COMMISSION.to_string())
-        .add_attribute("method", "bid"))
+    Ok(CheckedBid {
+        denom: accepted_denom,
+        commission: bid_commission,
+        current_bid,
+        new_bid,
+        value,
+        highest_bidder,
+        highest_bid,
+        referrer,
+    })
 }
 
-fn close(deps: DepsMut, info: MessageInfo) -> Result<Response, BidError> {
+fn close(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, BidError> {
     if BID_CLOSED.load(deps.storage)? {
         return Err(BidError::BidAlreadyClosed);
     }
 
-    if OWNER.load(deps.storage)? != info.sender {
-        return Err(BidError::OnlyOwnerCanClose);
+    let status = auction_status(deps.storage, &env.block)?;
+    match status {
+        AuctionStatus::Paused { .. } => return Err(BidError::Paused),
+        AuctionStatus::Cancelled => return Err(BidError::AuctionCancelled),
+        _ => {}
     }
 
-    let highest_bidder = HIGHEST_BIDDER.load(deps.storage)?;
+    // the seller and admins can close at any time, operators only expired auctions
+    if OWNER.load(deps.storage)? != info.sender && !ADMINS.has(deps.storage, info.sender.clone()) {
+        if !OPERATORS.has(deps.storage, info.sender.clone()) {
+            return Err(BidError::OnlyOwnerCanClose);
+        }
+
+        if status != AuctionStatus::Expired {
+            return Err(BidError::AuctionNotExpired);
+        }
+    }
+
+    Ok(settle(deps, env)?.add_attribute("method", "close"))
+}
+
+// pays the winning bid out and closes the auction, shared with the sudo force close
+pub fn settle(deps: DepsMut, env: Env) -> Result<Response, BidError> {
+    let highest_bidder = update_highest_bidder(deps.storage, &env.block)?;
     if highest_bidder.is_none() {
         return Err(BidError::NoBidPresent);
     }
     let highest_bidder = highest_bidder.unwrap();
 
-    let bid_msg = CosmosMsg::Bank(BankMsg::Send {
-        to_address: OWNER.load(deps.storage)?.to_string(),
-        amount: vec![Coin {
-            denom: DENOM.load(deps.storage)?,
-            amount: BID_BY_ADDR.load(deps.storage, highest_bidder.clone())?,
-        }],
-    });
-
     BID_CLOSED.save(deps.storage, &true)?;
     BID_WINNER.save(deps.storage, &Some(highest_bidder.clone()))?;
 
+    let winning_bid = BID_BY_ADDR.load(deps.storage, highest_bidder.clone())?;
+    let closed_hooks = hook_msgs(
+        deps.as_ref(),
+        vec![AuctionHook::Closed {
+            winner: Some(highest_bidder.to_string()),
+            amount: winning_bid,
+        }],
+    )?;
+    let event = AuctionClosed {
+        auction_id: env.contract.address.to_string(),
+        winner: Some(highest_bidder.to_string()),
+        denom: bid_denom(deps.storage, &highest_bidder)?,
+        amount: winning_bid,
+        commission: held_commission(deps.as_ref(), &highest_bidder)?,
+    };
+    let unstake_msgs = unstake_msgs(deps.storage, &deps.querier, &env)?;
+
+    // a remote winner pays from its escrow on the controller chain, which sends
+    // it here to be paid out by CollectProceeds
+    if let Some(remote) = REMOTE_BIDDER_BY_ADDR.may_load(deps.storage, highest_bidder.clone())? {
+        let settle_msg = IbcMsg::SendPacket {
+            channel_id: remote.channel,
+            data: to_binary(&AuctionPacket::Settle {
+                bidder: remote.sender,
+                amount: winning_bid,
+                receiver: env.contract.address.to_string(),
+            })?,
+            timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(IBC_TIMEOUT)),
+        };
+        REMOTE_PROCEEDS_PENDING.save(deps.storage, &true)?;
+
+        return Ok(Response::new()
+            .add_message(settle_msg)
+            .add_messages(unstake_msgs)
+            .add_submessages(closed_hooks)
+            .add_event(event.to_event())
+            .add_attribute("winner", highest_bidder.to_string()));
+    }
+
+    // a staked winning bid is paid out once released
+    let response = if !stake_locked(deps.storage)? {
+        let escrow = escrow(deps.storage, highest_bidder.clone(), winning_bid)?;
+        remove_escrow(deps.storage, &escrow)?;
+        pay_winner(
+            deps.as_ref(),
+            &env,
+            &highest_bidder,
+            escrow.bid,
+            escrow.commission,
+        )?
+    } else {
+        Response::new().add_messages(unstake_msgs)
+    };
+
+    Ok(response
+        .add_submessages(closed_hooks)
+        .add_event(event.to_event())
+        .add_attribute("winner", highest_bidder.to_string()))
+}
+
+// royalty, proceeds and held commission of the winning bid, then the winner callback,
+// all in the denom the winner bid in. A slashed escrow pays what was released of it.
+pub fn pay_winner(
+    deps: Deps,
+    env: &Env,
+    winner: &Addr,
+    winning_bid: Uint128,
+    commission: Uint128,
+) -> Result<Response, BidError> {
+    let denom = bid_denom(deps.storage, winner)?;
+
+    // creator royalties are taken from the winning bid before the proceeds split
+    let royalty = royalty_payment(deps, winning_bid)?;
+    let royalty_amount = royalty
+        .as_ref()
+        .map(|royalty| royalty.royalty_amount)
+        .unwrap_or_default();
+    let seller_amount = winning_bid - royalty_amount;
+
+    let mut msgs = vec![];
+    if let Some(royalty) = &royalty {
+        msgs.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: royalty.address.clone(),
+            amount: coins(royalty.royalty_amount.u128(), &denom),
+        }));
+    }
+
+    let referrer = REFERRER_BY_ADDR.may_load(deps.storage, winner.clone())?;
+    msgs.extend(split_payment(
+        deps,
+        &denom,
+        seller_amount,
+        &PROCEEDS_SPLIT.load(deps.storage)?,
+        referrer.clone(),
+    )?);
+    msgs.extend(split_payment(
+        deps,
+        &denom,
+        commission,
+        &COMMISSION_SPLIT.load(deps.storage)?,
+        referrer,
+    )?);
+
+    // lets a dao or vault winning the auction take custody programmatically
+    let mut callbacks = vec![];
+    if NOTIFY_WINNER.load(deps.storage)? && deps.querier.query_wasm_contract_info(winner).is_ok() {
+        let callback = WasmMsg::Execute {
+            contract_addr: winner.to_string(),
+            msg: to_binary(&WinnerCallbackMsg::ReceiveAuctionWin {
+                auction_id: env.contract.address.to_string(),
+                amount: winning_bid,
+            })?,
+            funds: vec![],
+        };
+        callbacks.push(SubMsg::reply_on_error(callback, WINNER_CALLBACK_REPLY_ID));
+    }
+
+    let mut response = Response::new()
+        .add_messages(msgs)
+        .add_submessages(callbacks)
+        .add_attribute("seller_amount", seller_amount.to_string())
+        .add_attribute("royalty_amount", royalty_amount.to_string())
+        .add_attribute("denom", denom);
+    if let Some(royalty) = royalty {
+        response = response.add_attribute("royalty_recipient", royalty.address);
+    }
+
+    Ok(response)
+}
+
+// the escrow of a remote winner is paid out once the controller transferred it here
+fn collect_proceeds(deps: DepsMut, env: Env) -> Result<Response, BidError> {
+    if !REMOTE_PROCEEDS_PENDING.load(deps.storage)? {
+        return Err(BidError::NoProceedsPending);
+    }
+
+    if stake_locked(deps.storage)? {
+        return Err(BidError::StakeLocked);
+    }
+
+    let winner = BID_WINNER
+        .load(deps.storage)?
+        .ok_or(BidError::NoProceedsPending)?;
+    let winning_bid = BID_BY_ADDR.load(deps.storage, winner.clone())?;
+    // the proceeds come on top of what every other escrow is owed
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, DENOM.load(deps.storage)?)?
+        .amount;
+    if balance < owed_escrows(deps.storage)? + winning_bid {
+        return Err(BidError::ProceedsNotArrived);
+    }
+
+    REMOTE_PROCEEDS_PENDING.save(deps.storage, &false)?;
+
+    let commission = held_commission(deps.as_ref(), &winner)?;
+    Ok(
+        pay_winner(deps.as_ref(), &env, &winner, winning_bid, commission)?
+            .add_attribute("winner", winner)
+            .add_attribute("method", "collect_proceeds"),
+    )
+}
+
+// the funder of someone else's bid must be approved by it and allowed to take part too
+pub fn ensure_funder(
+    deps: Deps,
+    bidder: &Addr,
+    funder: &Addr,
+    current_bid: Uint128,
+) -> Result<(), BidError> {
+    // a cross chain funded bid is refunded on its origin chain, only its funder raises it
+    if funder == bidder {
+        if !current_bid.is_zero()
+            && FUNDER_BY_ADDR.has(deps.storage, bidder.clone())
+            && IBC_ORIGIN_BY_ADDR.has(deps.storage, bidder.clone())
+        {
+            return Err(BidError::FunderMismatch);
+        }
+        return Ok(());
+    }
+
+    if *funder == OWNER.load(deps.storage)? {
+        return Err(BidError::OwnerCannotBid);
+    }
+
+    if DENYLIST.has(deps.storage, funder.clone()) {
+        return Err(BidError::Banned);
+    }
+
+    ensure_gate_allows(deps, funder)?;
+
+    if !APPROVED_FUNDERS.has(deps.storage, (bidder.clone(), funder.clone())) {
+        return Err(BidError::FunderNotApproved);
+    }
+
+    // refunds go to a single funder
+    if !current_bid.is_zero() && refund_address(deps.storage, bidder)? != *funder {
+        return Err(BidError::FunderMismatch);
+    }
+
+    Ok(())
+}
+
+fn approve_funder(deps: DepsMut, info: MessageInfo, funder: String) -> Result<Response, BidError> {
+    let funder = deps.api.addr_validate(&funder)?;
+    APPROVED_FUNDERS.save(deps.storage, (info.sender.clone(), funder.clone()), &())?;
+
+    Ok(Response::new()
+        .add_attribute("bidder", info.sender)
+        .add_attribute("funder", funder)
+        .add_attribute("method", "approve_funder"))
+}
+
+// bids already funded stay, the funder can no longer raise them
+fn revoke_funder(deps: DepsMut, info: MessageInfo, funder: String) -> Result<Response, BidError> {
+    let key = (info.sender.clone(), deps.api.addr_validate(&funder)?);
+    if !APPROVED_FUNDERS.has(deps.storage, key.clone()) {
+        return Err(BidError::FunderNotApproved);
+    }
+    APPROVED_FUNDERS.remove(deps.storage, key);
+
     Ok(Response::new()
-        .add_message(bid_msg)
-        .add_attribute("winner", highest_bidder.to_string())
-        .add_attribute("method", "close"))
+        .add_attribute("bidder", info.sender)
+        .add_attribute("funder", funder)
+        .add_attribute("method", "revoke_funder"))
+}
+
+// a withdrawn bid leaves the bidder as if it never bid
+fn clear_bid(storage: &mut dyn Storage, bidder: &Addr) {
+    BID_BY_ADDR.remove(storage, bidder.clone());
+    COMMISSION_BY_ADDR.remove(storage, bidder.clone());
+    BID_EXPIRES_AT.remove(storage, bidder.clone());
+    BID_DENOM_BY_ADDR.remove(storage, bidder.clone());
+    FUNDER_BY_ADDR.remove(storage, bidder.clone());
+    FUNDED_BY_ADDR.remove(storage, bidder.clone());
 }
 
 fn retract(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     receiver: Option<String>,
 ) -> Result<Response, BidError> {
-    if !BID_CLOSED.load(deps.storage)? {
-        return Err(BidError::BidNotClosed);
-    }
-
     let recipient = if let Some(addr) = receiver {
         deps.api.addr_validate(&addr)?
     } else {
-        info.sender
+        info.sender.clone()
+    };
+
+    let (running, withdrawal) = match auction_status(deps.storage, &env.block)? {
+        AuctionStatus::Closed => (false, false),
+        AuctionStatus::Paused { .. } => (true, false),
+        AuctionStatus::Cancelled => {
+            // persist a cancellation caused by a pause running too long
+            BID_CLOSED.save(deps.storage, &true)?;
+            BID_WINNER.save(deps.storage, &None)?;
+            (false, false)
+        }
+        // an expired bid can be withdrawn right away, the bidder is not out of the auction
+        AuctionStatus::Open | AuctionStatus::Expired => {
+            if !bid_expired(deps.as_ref(), &env, &recipient)? {
+                return Err(BidError::BidNotClosed);
+            }
+            (true, true)
+        }
+        AuctionStatus::Frozen => return Err(BidError::Frozen),
     };
 
-    if BID_WINNER.load(deps.storage)?.unwrap() == recipient {
+    // anyone can trigger a refund once the auction is over, not before
+    if running
+        && info.sender != recipient
+        && info.sender != refund_address(deps.storage, &recipient)?
+    {
+        return Err(BidError::OnlyBidderCanRetract);
+    }
+
+    // a funder taking its bid back leaves the bidder free to bid again
+    let withdrawal = withdrawal || (running && info.sender != recipient);
+
+    // no winner when the auction was cancelled
+    if BID_WINNER.load(deps.storage)? == Some(recipient.clone()) {
         return Err(BidError::BidWinner);
     }
 
+    // only bids that are not leading are refundable while the auction runs
+    if running && highest_bidder(deps.storage, &env.block)? == Some(recipient.clone()) {
+        return Err(BidError::BidWinner);
+    }
+
+    // their escrow is held by the controller chain
+    if REMOTE_BIDDER_BY_ADDR.has(deps.storage, recipient.clone()) {
+        return Err(BidError::RemoteBid);
+    }
+
+    if stake_locked(deps.storage)? {
+        return Err(BidError::StakeLocked);
+    }
+
+    ensure_gate_allows(deps.as_ref(), &recipient)?;
+
     if BID_RETRACTED_FOR_ADDR.has(deps.storage, recipient.clone()) {
         return Err(BidError::RetractAlreadyDone);
+    }
+
+    let event = BidRetracted {
+        auction_id: env.contract.address.to_string(),
+        bidder: recipient.to_string(),
+        recipient: refund_address(deps.storage, &recipient)?.to_string(),
+        denom: bid_denom(deps.storage, &recipient)?,
+        amount: BID_BY_ADDR
+            .load(deps.storage, recipient.clone())
+            .map_err(|_| BidError::NoBidPresent)?,
+        commission: held_commission(deps.as_ref(), &recipient)?,
+    };
+    let escrow = escrow(deps.storage, recipient.clone(), event.amount)?;
+    remove_escrow(deps.storage, &escrow)?;
+    let rewards = take_reward_share(deps.storage, &recipient, &event.denom, event.amount)?;
+    let bid = take_released(
+        deps.storage,
+        &recipient,
+        &event.denom,
+        event.amount + event.commission,
+    )? + rewards;
+
+    let refund = Coin {
+        denom: event.denom.clone(),
+        amount: bid,
+    };
+
+    let shares = refund_shares(deps.storage, &recipient, refund.amount)?;
+    // once released the stake pools no longer count the escrow, a failed cross chain
+    // refund is then kept as a deposit instead of going back in escrow
+    let gone = withdrawal || REWARDS.load(deps.storage)?.is_some();
+    if withdrawal {
+        clear_bid(deps.storage, &recipient);
     } else {
         BID_RETRACTED_FOR_ADDR.save(deps.storage, recipient.clone(), &())?;
     }
 
-    let bid = BID_BY_ADDR
-        .load(deps.storage, recipient.clone())
-        .map_err(|_| BidError::NoBidPresent)?;
+    // cross chain bidders are refunded on their origin chain, the reply keeps the
+    // packet sequence so a failed transfer puts the bid back in escrow
+    let retract_msgs = match IBC_ORIGIN_BY_ADDR.may_load(deps.storage, recipient.clone())? {
+        Some(origin) => {
+            PENDING_IBC_REFUND.save(
+                deps.storage,
+                &IbcRefund {
+                    bidder: recipient.clone(),
+                    recipient: deps.api.addr_validate(&event.recipient)?,
+                    withdrawn: gone.then(|| refund.clone()),
+                },
+            )?;
+            vec![SubMsg::reply_on_success(
+                transfer_with_callback(
+                    &origin.channel,
+                    &env.contract.address,
+                    &origin.sender,
+                    &refund,
+                    env.block.time.plus_seconds(IBC_TIMEOUT),
+                ),
+                IBC_REFUND_REPLY_ID,
+            )]
+        }
+        None => shares
+            .into_iter()
+            .map(|(to, amount)| {
+                SubMsg::new(BankMsg::Send {
+                    to_address: to.to_string(),
+                    amount: coins(amount.u128(), &refund.denom),
+                })
+            })
+            .collect(),
+    };
 
-    let retract_msg = CosmosMsg::Bank(BankMsg::Send {
-        to_address: recipient.to_string(),
-        amount: vec![Coin {
-            denom: DENOM.load(deps.storage)?,
-            amount: bid,
-        }],
-    });
+    let hooks = vec![AuctionHook::Retracted {
+        bidder: recipient.to_string(),
+        amount: bid,
+    }];
 
     Ok(Response::new()
-        .add_message(retract_msg)
+        .add_submessages(retract_msgs)
+        .add_submessages(hook_msgs(deps.as_ref(), hooks)?)
+        .add_event(event.to_event())
         .add_attribute("amount", bid.to_string())
-        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("rewards", rewards.to_string())
+        .add_attribute("recipient", &event.recipient)
         .add_attribute("method", "retract"))
 }
+
+fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_owner: String,
+    expiry: Option<Expiration>,
+) -> Result<Response, BidError> {
+    if OWNER.load(deps.storage)? != info.sender {
+        return Err(BidError::OnlyOwner);
+    }
+
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+
+    // the owner receives the proceeds, a bidder cannot become one
+    if BID_BY_ADDR.has(deps.storage, new_owner.clone()) {
+        return Err(BidError::OwnerCannotBid);
+    }
+
+    if let Some(expiry) = &expiry {
+        if expiry.is_expired(&env.block) {
+            return Err(BidError::OwnershipProposalExpired);
+        }
+    }
+
+    PENDING_OWNER.save(
+        deps.storage,
+        &PendingOwnership {
+            new_owner: new_owner.clone(),
+            expiry,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("pending_owner", new_owner)
+        .add_attribute("method", "propose_new_owner"))
+}
+
+fn accept_ownership(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, BidError> {
+    let pending = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(BidError::NoPendingOwner)?;
+
+    if pending.new_owner != info.sender {
+        return Err(BidError::OnlyPendingOwner);
+    }
+
+    if let Some(expiry) = &pending.expiry {
+        if expiry.is_expired(&env.block) {
+            return Err(BidError::OwnershipProposalExpired);
+        }
+    }
+
+    // it may have bid since the proposal
+    if BID_BY_ADDR.has(deps.storage, pending.new_owner.clone()) {
+        return Err(BidError::OwnerCannotBid);
+    }
+
+    // the admin role granted by default to the owner moves with it
+    let previous = OWNER.load(deps.storage)?;
+    if ADMINS.has(deps.storage, previous.clone()) {
+        ADMINS.remove(deps.storage, previous);
+        ADMINS.save(deps.storage, pending.new_owner.clone(), &())?;
+    }
+
+    OWNER.save(deps.storage, &pending.new_owner)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("owner", pending.new_owner)
+        .add_attribute("method", "accept_ownership"))
+}
+
+fn cancel_ownership_proposal(deps: DepsMut, info: MessageInfo) -> Result<Response, BidError> {
+    if OWNER.load(deps.storage)? != info.sender {
+        return Err(BidError::OnlyOwner);
+    }
+
+    if PENDING_OWNER.may_load(deps.storage)?.is_none() {
+        return Err(BidError::NoPendingOwner);
+    }
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("method", "cancel_ownership_proposal"))
+}
+
+fn renounce_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, BidError> {
+    if OWNER.load(deps.storage)? != info.sender {
+        return Err(BidError::OnlyOwner);
+    }
+
+    // proceeds and commissions are routed to the owner until the bid is closed
+    if !BID_CLOSED.load(deps.storage)? {
+        return Err(BidError::BidNotClosed);
+    }
+
+    // and until a staked or remote winning bid is paid out
+    if stake_locked(deps.storage)? {
+        return Err(BidError::StakeLocked);
+    }
+    if REMOTE_PROCEEDS_PENDING.load(deps.storage)? {
+        return Err(BidError::ProceedsPending);
+    }
+
+    OWNER.remove(deps.storage);
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("method", "renounce_ownership"))
+}
+
+fn add_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    role: Role,
+) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    role_members(&role).save(deps.storage, address.clone(), &())?;
+
+    Ok(Response::new()
+        .add_attribute("address", address)
+        .add_attribute("role", role_name(&role))
+        .add_attribute("method", "add_role"))
+}
+
+fn remove_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    role: Role,
+) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    let members = role_members(&role);
+    members.remove(deps.storage, address.clone());
+
+    // keep at least one admin so roles stay manageable
+    if role == Role::Admin
+        && ADMINS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_none()
+    {
+        return Err(BidError::LastAdmin);
+    }
+
+    Ok(Response::new()
+        .add_attribute("address", address)
+        .add_attribute("role", role_name(&role))
+        .add_attribute("method", "remove_role"))
+}
+
+fn set_expiration(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    expiration: Option<Expiration>,
+) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    if BID_CLOSED.load(deps.storage)? {
+        return Err(BidError::BidClosed);
+    }
+
+    if let Some(expiration) = &expiration {
+        if expiration.is_expired(&env.block) {
+            return Err(BidError::AuctionExpired);
+        }
+    }
+
+    EXPIRATION.save(deps.storage, &expiration)?;
+
+    Ok(Response::new().add_attribute("method", "set_expiration"))
+}
+
+fn cancel(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    Ok(cancel_auction(deps, env)?.add_attribute("method", "cancel"))
+}
+
+// shared with the sudo force cancel
+pub fn cancel_auction(deps: DepsMut, env: Env) -> Result<Response, BidError> {
+    if BID_CLOSED.load(deps.storage)? {
+        return Err(BidError::BidAlreadyClosed);
+    }
+
+    BID_CLOSED.save(deps.storage, &true)?;
+    BID_WINNER.save(deps.storage, &None)?;
+
+    let hooks = vec![AuctionHook::Closed {
+        winner: None,
+        amount: Uint128::zero(),
+    }];
+
+    let event = AuctionClosed {
+        auction_id: env.contract.address.to_string(),
+        winner: None,
+        denom: DENOM.load(deps.storage)?,
+        amount: Uint128::zero(),
+        commission: Uint128::zero(),
+    };
+
+    Ok(Response::new()
+        .add_messages(unstake_msgs(deps.storage, &deps.querier, &env)?)
+        .add_submessages(hook_msgs(deps.as_ref(), hooks)?)
+        .add_event(event.to_event()))
+}
+
+fn pause(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    match auction_status(deps.storage, &env.block)? {
+        AuctionStatus::Open | AuctionStatus::Expired => {}
+        AuctionStatus::Paused { .. } => return Err(BidError::Paused),
+        AuctionStatus::Closed => return Err(BidError::BidAlreadyClosed),
+        AuctionStatus::Cancelled => return Err(BidError::AuctionCancelled),
+        AuctionStatus::Frozen => return Err(BidError::Frozen),
+    }
+
+    PAUSED_AT.save(deps.storage, &Some(env.block.time))?;
+
+    Ok(Response::new().add_attribute("method", "pause"))
+}
+
+fn unpause(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    match auction_status(deps.storage, &env.block)? {
+        AuctionStatus::Paused { .. } => {}
+        AuctionStatus::Cancelled => return Err(BidError::AuctionCancelled),
+        _ => return Err(BidError::NotPaused),
+    }
+
+    PAUSED_AT.save(deps.storage, &None)?;
+    AWAITING_CONTROLLER.save(deps.storage, &false)?;
+
+    Ok(Response::new().add_attribute("method", "unpause"))
+}
+
+fn set_allowlist_only(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    ALLOWLIST_ONLY.save(deps.storage, &enabled)?;
+
+    Ok(Response::new()
+        .add_attribute("enabled", enabled.to_string())
+        .add_attribute("method", "set_allowlist_only"))
+}
+
+fn update_address_list(
+    deps: DepsMut,
+    info: MessageInfo,
+    list: Map<Addr, ()>,
+    add: Vec<String>,
+    remove: Vec<String>,
+    method: &str,
+) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    for address in &add {
+        list.save(deps.storage, deps.api.addr_validate(address)?, &())?;
+    }
+    for address in &remove {
+        list.remove(deps.storage, deps.api.addr_validate(address)?);
+    }
+
+    Ok(Response::new()
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string())
+        .add_attribute("method", method))
+}
+
+fn set_merkle_root(
+    deps: DepsMut,
+    info: MessageInfo,
+    root: Option<String>,
+) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    let root = validate_merkle_root(root)?;
+    MERKLE_ROOT.save(deps.storage, &root)?;
+
+    Ok(Response::new()
+        .add_attribute("root", root.unwrap_or_default())
+        .add_attribute("method", "set_merkle_root"))
+}
+
+fn set_gate(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: Option<String>,
+) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    let gate = address
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    GATE.save(deps.storage, &gate)?;
+
+    Ok(Response::new()
+        .add_attribute("gate", gate.map(String::from).unwrap_or_default())
+        .add_attribute("method", "set_gate"))
+}
+
+fn add_hook(deps: DepsMut, info: MessageInfo, address: String) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    let hook = deps.api.addr_validate(&address)?;
+    if HOOKS.has(deps.storage, hook.clone()) {
+        return Err(BidError::HookAlreadyRegistered);
+    }
+    HOOKS.save(deps.storage, hook.clone(), &())?;
+
+    Ok(Response::new()
+        .add_attribute("hook", hook)
+        .add_attribute("method", "add_hook"))
+}
+
+fn remove_hook(deps: DepsMut, info: MessageInfo, address: String) -> Result<Response, BidError> {
+    ensure_admin(deps.as_ref(), &info.sender)?;
+
+    let hook = deps.api.addr_validate(&address)?;
+    if !HOOKS.has(deps.storage, hook.clone()) {
+        return Err(BidError::HookNotRegistered);
+    }
+    HOOKS.remove(deps.storage, hook.clone());
+
+    Ok(Response::new()
+        .add_attribute("hook", hook)
+        .add_attribute("method", "remove_hook"))
+}
+
+// bidders commit to the listing as it is when they bid
+fn update_metadata(
+    deps: DepsMut,
+    info: MessageInfo,
+    metadata: Option<AuctionMetadata>,
+) -> Result<Response, BidError> {
+    if OWNER.load(deps.storage)? != info.sender {
+        return Err(BidError::OnlyOwner);
+    }
+
+    if HIGHEST_BIDDER.load(deps.storage)?.is_some() {
+        return Err(BidError::MetadataLocked);
+    }
+
+    METADATA.save(deps.storage, &validate_metadata(metadata)?)?;
+
+    Ok(Response::new().add_attribute("method", "update_metadata"))
+}
+
+// one capped sub message per subscriber and event, replied to only on failure
+pub fn hook_msgs(deps: Deps, hooks: Vec<AuctionHook>) -> Result<Vec<SubMsg>, BidError> {
+    let subscribers = HOOKS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut msgs = vec![];
+    for hook in hooks {
+        let msg = to_binary(&AuctionHookMsg::AuctionHook(hook))?;
+        for subscriber in &subscribers {
+            let execute = WasmMsg::Execute {
+                contract_addr: subscriber.to_string(),
+                msg: msg.clone(),
+                funds: vec![],
+            };
+            msgs.push(
+                SubMsg::reply_on_error(execute, HOOK_REPLY_ID).with_gas_limit(HOOK_GAS_LIMIT),
+            );
+        }
+    }
+
+    Ok(msgs)
+}
+
+// denylist, allowlist or merkle proof, then the gate, for local and remote bidders
+pub fn ensure_allowed(
+    deps: Deps,
+    bidder: &Addr,
+    proof: Option<Vec<String>>,
+) -> Result<(), BidError> {
+    if DENYLIST.has(deps.storage, bidder.clone()) {
+        return Err(BidError::Banned);
+    }
+
+    // allowlisted bidders are either stored or proven against the merkle root
+    let merkle_root = MERKLE_ROOT.load(deps.storage)?;
+    if (ALLOWLIST_ONLY.load(deps.storage)? || merkle_root.is_some())
+        && !ALLOWLIST.has(deps.storage, bidder.clone())
+    {
+        match (merkle_root, proof) {
+            (Some(root), Some(proof)) => {
+                if !merkle::verify(&root, bidder.as_str(), &proof).unwrap_or_default() {
+                    return Err(BidError::InvalidMerkleProof);
+                }
+            }
+            _ => return Err(BidError::NotWhitelisted),
+        }
+    }
+
+    ensure_gate_allows(deps, bidder)
+}
+
+fn ensure_gate_allows(deps: Deps, address: &Addr) -> Result<(), BidError> {
+    if let Some(gate) = GATE.load(deps.storage)? {
+        let allowed: bool = deps.querier.query_wasm_smart(
+            gate,
+            &GateQueryMsg::IsAllowed {
+                address: address.to_string(),
+            },
+        )?;
+
+        if !allowed {
+            return Err(BidError::RejectedByGate);
+        }
+    }
+
+    Ok(())
+}
+
+fn ensure_admin(deps: Deps, sender: &Addr) -> Result<(), BidError> {
+    if !ADMINS.has(deps.storage, sender.clone()) {
+        return Err(BidError::OnlyAdmin);
+    }
+
+    Ok(())
+}
+
+fn role_name(role: &Role) -> &'static str {
+    match role {
+        Role::Admin => "admin",
+        Role::Operator => "operator",
+    }
+}
+
+// drops the expired and retracted bids on top of the leaders, the next valid one takes the lead
+pub fn update_highest_bidder(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+) -> StdResult<Option<Addr>> {
+    while let Some(leader) = LEADERS.back(storage)? {
+        if bid_valid(storage, &leader, block)? {
+            HIGHEST_BIDDER.save(storage, &Some(leader.clone()))?;
+            return Ok(Some(leader));
+        }
+
+        LEADERS.pop_back(storage)?;
+    }
+
+    HIGHEST_BIDDER.save(storage, &None)?;
+    Ok(None)
+}
+
+fn bid_expired(deps: Deps, env: &Env, bidder: &Addr) -> StdResult<bool> {
+    Ok(BID_EXPIRES_AT
+        .may_load(deps.storage, bidder.clone())?
+        .map(|expires_at| expires_at.is_expired(&env.block))
+        .unwrap_or_default())
+}
+
+// the single accepted denom present in the funds of a bid
+fn funds_denom(deps: Deps, funds: &[Coin]) -> Result<String, BidError> {
+    let quote = DENOM.load(deps.storage)?;
+    let mut denoms = funds
+        .iter()
+        .filter(|coin| !coin.amount.is_zero())
+        .map(|coin| coin.denom.clone())
+        .filter(|denom| *denom == quote || ACCEPTED_ASSETS.has(deps.storage, denom.clone()));
+
+    match (denoms.next(), denoms.next()) {
+        (Some(denom), None) => Ok(denom),
+        (Some(_), Some(_)) => Err(BidError::MultipleDenoms),
+        (None, _) => Err(BidError::NotEnoughCoinForCommission),
+    }
+}
+
+fn commission_for(storage: &dyn Storage, denom: &str) -> StdResult<Uint128> {
+    if denom == DENOM.load(storage)? {
+        COMMISSION.load(storage)
+    } else {
+        ACCEPTED_ASSETS.load(storage, denom.to_string())
+    }
+}
+
+// value of an amount of an accepted denom in the quote denom
+pub fn bid_value(deps: Deps, denom: &str, amount: Uint128) -> StdResult<Uint128> {
+    if denom == DENOM.load(deps.storage)? {
+        return Ok(amount);
+    }
+
+    // instantiate requires an oracle along with other accepted denoms
+    let oracle = ORACLE
+        .load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("no oracle to price bids with"))?;
+    let price: PriceResponse = deps.querier.query_wasm_smart(
+        oracle,
+        &OracleQueryMsg::Price {
+            denom: denom.to_string(),
+        },
+    )?;
+
+    Ok(amount * price.rate)
+}
+
+// commission still held by the contract for the given bidder
+fn held_commission(deps: Deps, bidder: &Addr) -> Result<Uint128, BidError> {
+    match COMMISSION_POLICY.load(deps.storage)? {
+        CommissionPolicy::Kept => Ok(Uint128::zero()),
+        CommissionPolicy::Refundable => Ok(COMMISSION_BY_ADDR
+            .may_load(deps.storage, bidder.clone())?
+            .flatten()
+            .unwrap_or_default()),
+    }
+}
+
+// royalty owed to the item creator, capped to the sale price
+fn royalty_payment(
+    deps: Deps,
+    sale_price: Uint128,
+) -> Result<Option<RoyaltiesInfoResponse>, BidError> {
+    if !PAY_ROYALTIES.load(deps.storage)? {
+        return Ok(None);
+    }
+
+    let item = AUCTION_ITEM
+        .load(deps.storage)?
+        .ok_or(BidError::RoyaltiesWithoutItem)?;
+    let mut royalty: RoyaltiesInfoResponse = deps.querier.query_wasm_smart(
+        item.contract,
+        &Cw721QueryMsg::Extension {
+            msg: Cw2981QueryMsg::RoyaltyInfo {
+                token_id: item.token_id,
+                sale_price,
+            },
+        },
+    )?;
+
+    if royalty.address.is_empty() || royalty.royalty_amount.is_zero() {
+        return Ok(None);
+    }
+
+    royalty.address = deps.api.addr_validate(&royalty.address)?.to_string();
+    royalty.royalty_amount = royalty.royalty_amount.min(sale_price);
+
+    Ok(Some(royalty))
+}
+
+// one bank message per recipient of the split, the rounding remainder goes to the last one
+fn split_payment(
+    deps: Deps,
+    denom: &str,
+    amount: Uint128,
+    split: &[FeeShare],
+    referrer: Option<Addr>,
+) -> Result<Vec<CosmosMsg>, BidError> {
+    let owner = OWNER.load(deps.storage)?;
+
+    let mut remaining = amount;
+    let mut msgs = vec![];
+    for (idx, share) in split.iter().enumerate() {
+        let part = if idx == split.len() - 1 {
+            remaining
+        } else {
+            amount * share.weight
+        };
+        remaining -= part;
+
+        if part.is_zero() {
+            continue;
+        }
+
+        let to_address = match &share.recipient {
+            FeeRecipient::Owner {} => owner.to_string(),
+            FeeRecipient::Referrer {} => referrer.as_ref().unwrap_or(&owner).to_string(),
+            FeeRecipient::Address { address } => address.clone(),
+        };
+
+        msgs.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address,
+            amount: coins(part.u128(), denom),
+        }));
+    }
+
+    Ok(msgs)
+}
@@ -0,0 +1,372 @@
+use crate::contract::execute::{bid_value, ensure_allowed, hook_msgs, update_highest_bidder};
+use crate::error::BidError;
+use crate::events::{AuctionClosed, AuctionReopened, BidPlaced, BidRetracted};
+use crate::hooks::AuctionHook;
+use crate::msg::{AuctionStatus, IbcOrigin};
+use crate::packet::{remote_bidder, AuctionPacket, ControllerPacket, PacketAck, IBC_VERSION};
+use crate::state::{
+    auction_status, bid_denom, highest_bidder, AWAITING_CONTROLLER, BID_BY_ADDR, BID_CLOSED,
+    BID_RETRACTED_FOR_ADDR, BID_WINNER, CONTROLLER_CHANNEL, DENOM, EXPIRATION, FROZEN,
+    HIGHEST_BIDDER, LEADERS, PAUSED_AT, REMOTE_BIDDER_BY_ADDR, REMOTE_PROCEEDS_PENDING, UNBONDING,
+};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, DepsMut, Env, IbcBasicResponse, IbcChannel,
+    IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcOrder, IbcPacket,
+    IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, Uint128,
+};
+use cw_utils::Expiration;
+
+pub fn _ibc_channel_open(deps: DepsMut, msg: IbcChannelOpenMsg) -> Result<(), BidError> {
+    validate_channel(msg.channel(), msg.counterparty_version())?;
+
+    // an auction is driven by a single controller
+    if CONTROLLER_CHANNEL.load(deps.storage)?.is_some() {
+        return Err(BidError::InvalidChannel {
+            reason: "controller already connected".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+pub fn _ibc_channel_connect(
+    deps: DepsMut,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, BidError> {
+    validate_channel(msg.channel(), msg.counterparty_version())?;
+
+    let channel_id = msg.channel().endpoint.channel_id.clone();
+    CONTROLLER_CHANNEL.save(deps.storage, &Some(channel_id.clone()))?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("channel", channel_id)
+        .add_attribute("method", "ibc_channel_connect"))
+}
+
+// remote bids stay in the auction, the controller can reconnect to settle them
+pub fn _ibc_channel_close(
+    deps: DepsMut,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, BidError> {
+    let channel_id = msg.channel().endpoint.channel_id.clone();
+    if CONTROLLER_CHANNEL.load(deps.storage)? == Some(channel_id.clone()) {
+        CONTROLLER_CHANNEL.save(deps.storage, &None)?;
+    }
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("channel", channel_id)
+        .add_attribute("method", "ibc_channel_close"))
+}
+
+// errors are acked back to the controller so it can release the escrow
+pub fn _ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, BidError> {
+    match receive_packet(deps, env, msg.packet) {
+        Ok(response) => Ok(response),
+        Err(err) => Ok(IbcReceiveResponse::new()
+            .set_ack(to_binary(&PacketAck::Error(err.to_string()))?)
+            .add_attribute("error", err.to_string())
+            .add_attribute("method", "ibc_packet_receive")),
+    }
+}
+
+pub fn _ibc_packet_ack(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, BidError> {
+    match from_binary(&msg.acknowledgement.data)? {
+        PacketAck::Result(_) => {
+            Ok(IbcBasicResponse::new().add_attribute("method", "ibc_packet_ack"))
+        }
+        PacketAck::Error(err) => Ok(rollback(deps, env, &msg.original_packet)?
+            .add_attribute("error", err)
+            .add_attribute("method", "ibc_packet_ack")),
+    }
+}
+
+pub fn _ibc_packet_timeout(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, BidError> {
+    Ok(rollback(deps, env, &msg.packet)?.add_attribute("method", "ibc_packet_timeout"))
+}
+
+fn validate_channel(
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), BidError> {
+    if channel.order != IbcOrder::Unordered {
+        return Err(BidError::InvalidChannel {
+            reason: "only unordered channels are supported".to_string(),
+        });
+    }
+
+    if channel.version != IBC_VERSION || counterparty_version.unwrap_or(IBC_VERSION) != IBC_VERSION
+    {
+        return Err(BidError::InvalidChannel {
+            reason: format!("version must be {}", IBC_VERSION),
+        });
+    }
+
+    Ok(())
+}
+
+fn receive_packet(
+    mut deps: DepsMut,
+    env: Env,
+    packet: IbcPacket,
+) -> Result<IbcReceiveResponse, BidError> {
+    let channel = packet.dest.channel_id;
+    if CONTROLLER_CHANNEL.load(deps.storage)? != Some(channel.clone()) {
+        return Err(BidError::InvalidChannel {
+            reason: "not the controller channel".to_string(),
+        });
+    }
+
+    if FROZEN.load(deps.storage)? {
+        return Err(BidError::Frozen);
+    }
+
+    let auction_id = env.contract.address.to_string();
+    let (amount, hooks, event, method) = match from_binary(&packet.data)? {
+        ControllerPacket::Bid {
+            bidder,
+            amount,
+            proof,
+        } => {
+            let (event, hooks) = remote_bid(deps.branch(), env, channel, bidder, amount, proof)?;
+            (event.new_total, hooks, event.to_event(), "ibc_bid")
+        }
+        ControllerPacket::Retract { bidder } => {
+            let amount = remote_retract(deps.branch(), env, &channel, &bidder)?;
+            let bidder = remote_bidder(&channel, &bidder).to_string();
+            let event = BidRetracted {
+                auction_id,
+                bidder: bidder.clone(),
+                recipient: bidder.clone(),
+                denom: DENOM.load(deps.storage)?,
+                amount,
+                commission: Uint128::zero(),
+            };
+            let hooks = vec![AuctionHook::Retracted { bidder, amount }];
+            (amount, hooks, event.to_event(), "ibc_retract")
+        }
+        ControllerPacket::OpenAuction { expiration } => {
+            remote_open(deps, env, expiration)?;
+            return Ok(IbcReceiveResponse::new()
+                .set_ack(to_binary(&PacketAck::Result(Binary::default()))?)
+                .add_attribute("method", "ibc_open_auction"));
+        }
+    };
+
+    Ok(IbcReceiveResponse::new()
+        .set_ack(to_binary(&PacketAck::Result(to_binary(&amount)?))?)
+        .add_submessages(hook_msgs(deps.as_ref(), hooks)?)
+        .add_event(event)
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("method", method))
+}
+
+fn remote_open(deps: DepsMut, env: Env, expiration: Option<Expiration>) -> Result<(), BidError> {
+    if !AWAITING_CONTROLLER.load(deps.storage)? {
+        return Err(BidError::NotAwaitingController);
+    }
+
+    // a pause past the max duration cancelled it
+    if auction_status(deps.storage, &env.block)? == AuctionStatus::Cancelled {
+        return Err(BidError::AuctionCancelled);
+    }
+
+    if let Some(expiration) = expiration {
+        if expiration.is_expired(&env.block) {
+            return Err(BidError::AuctionExpired);
+        }
+        EXPIRATION.save(deps.storage, &Some(expiration))?;
+    }
+
+    AWAITING_CONTROLLER.save(deps.storage, &false)?;
+    PAUSED_AT.save(deps.storage, &None)?;
+
+    Ok(())
+}
+
+// remote bids pay no commission here, the controller charges its own
+fn remote_bid(
+    deps: DepsMut,
+    env: Env,
+    channel: String,
+    bidder: String,
+    amount: Uint128,
+    proof: Option<Vec<String>>,
+) -> Result<(BidPlaced, Vec<AuctionHook>), BidError> {
+    if BID_CLOSED.load(deps.storage)? {
+        return Err(BidError::BidClosed);
+    }
+
+    match auction_status(deps.storage, &env.block)? {
+        AuctionStatus::Expired => return Err(BidError::AuctionExpired),
+        AuctionStatus::Paused { .. } => return Err(BidError::Paused),
+        AuctionStatus::Cancelled => return Err(BidError::AuctionCancelled),
+        AuctionStatus::Frozen => return Err(BidError::Frozen),
+        AuctionStatus::Open | AuctionStatus::Closed => {}
+    }
+
+    let addr = remote_bidder(&channel, &bidder);
+    if BID_RETRACTED_FOR_ADDR.has(deps.storage, addr.clone()) {
+        return Err(BidError::RetractAlreadyDone);
+    }
+
+    ensure_allowed(deps.as_ref(), &addr, proof)?;
+
+    let highest_bidder = update_highest_bidder(deps.storage, &env.block)?;
+    let highest_bid = match &highest_bidder {
+        Some(highest_bidder) => BID_BY_ADDR.load(deps.storage, highest_bidder.clone())?,
+        None => Uint128::zero(),
+    };
+    // remote bids are escrowed in the quote denom, others are compared by value
+    let highest_value = match &highest_bidder {
+        Some(highest_bidder) => bid_value(
+            deps.as_ref(),
+            &bid_denom(deps.storage, highest_bidder)?,
+            highest_bid,
+        )?,
+        None => Uint128::zero(),
+    };
+    let new_bid = BID_BY_ADDR
+        .may_load(deps.storage, addr.clone())?
+        .unwrap_or_default()
+        + amount;
+    if new_bid <= highest_value {
+        return Err(BidError::BidTooLow);
+    }
+
+    let event = BidPlaced {
+        auction_id: env.contract.address.to_string(),
+        bidder: addr.to_string(),
+        denom: DENOM.load(deps.storage)?,
+        increment: amount,
+        new_total: new_bid,
+        previous_leader: highest_bidder.as_ref().map(Addr::to_string),
+        commission: Uint128::zero(),
+        funder: None,
+    };
+
+    let mut hooks = vec![AuctionHook::BidPlaced {
+        bidder: addr.to_string(),
+        amount: new_bid,
+    }];
+    if let Some(previous) = highest_bidder.filter(|bidder| *bidder != addr) {
+        hooks.push(AuctionHook::Outbid {
+            bidder: previous.to_string(),
+            amount: highest_bid,
+        });
+    }
+
+    REMOTE_BIDDER_BY_ADDR.save(
+        deps.storage,
+        addr.clone(),
+        &IbcOrigin {
+            channel,
+            sender: bidder,
+        },
+    )?;
+    LEADERS.push_back(deps.storage, &addr)?;
+    HIGHEST_BIDDER.save(deps.storage, &Some(addr.clone()))?;
+    BID_BY_ADDR.save(deps.storage, addr, &new_bid)?;
+
+    Ok((event, hooks))
+}
+
+fn remote_retract(
+    deps: DepsMut,
+    env: Env,
+    channel: &str,
+    bidder: &str,
+) -> Result<Uint128, BidError> {
+    let addr = remote_bidder(channel, bidder);
+
+    let paused = match auction_status(deps.storage, &env.block)? {
+        AuctionStatus::Closed => false,
+        AuctionStatus::Paused { .. } => true,
+        AuctionStatus::Cancelled => {
+            BID_CLOSED.save(deps.storage, &true)?;
+            BID_WINNER.save(deps.storage, &None)?;
+            false
+        }
+        AuctionStatus::Open | AuctionStatus::Expired => return Err(BidError::BidNotClosed),
+        AuctionStatus::Frozen => return Err(BidError::Frozen),
+    };
+
+    if BID_WINNER.load(deps.storage)? == Some(addr.clone())
+        || (paused && highest_bidder(deps.storage, &env.block)? == Some(addr.clone()))
+    {
+        return Err(BidError::BidWinner);
+    }
+
+    if BID_RETRACTED_FOR_ADDR.has(deps.storage, addr.clone()) {
+        return Err(BidError::RetractAlreadyDone);
+    }
+
+    // failures are acked, so the retract is only recorded once every check passed
+    let bid = BID_BY_ADDR
+        .load(deps.storage, addr.clone())
+        .map_err(|_| BidError::NoBidPresent)?;
+    BID_RETRACTED_FOR_ADDR.save(deps.storage, addr, &())?;
+
+    Ok(bid)
+}
+
+// a settlement the controller did not take reopens the close
+// an unbonding cannot be undone, once the close started it the auction is
+// cancelled instead of reopened and every bid is refunded once released
+fn rollback(deps: DepsMut, env: Env, packet: &IbcPacket) -> Result<IbcBasicResponse, BidError> {
+    let AuctionPacket::Settle { bidder, .. } = from_binary(&packet.data)?;
+
+    let winner = remote_bidder(&packet.src.channel_id, &bidder);
+    if BID_WINNER.load(deps.storage)? != Some(winner.clone()) {
+        return Ok(IbcBasicResponse::new());
+    }
+
+    BID_WINNER.save(deps.storage, &None)?;
+    REMOTE_PROCEEDS_PENDING.save(deps.storage, &false)?;
+    let auction_id = env.contract.address.to_string();
+
+    if UNBONDING.load(deps.storage)?.is_some() {
+        let hooks = vec![AuctionHook::Closed {
+            winner: None,
+            amount: Uint128::zero(),
+        }];
+        let event = AuctionClosed {
+            auction_id,
+            winner: None,
+            denom: DENOM.load(deps.storage)?,
+            amount: Uint128::zero(),
+            commission: Uint128::zero(),
+        };
+
+        return Ok(IbcBasicResponse::new()
+            .add_submessages(hook_msgs(deps.as_ref(), hooks)?)
+            .add_event(event.to_event())
+            .add_attribute("cancelled", winner.to_string()));
+    }
+
+    BID_CLOSED.save(deps.storage, &false)?;
+
+    let hooks = vec![AuctionHook::Reopened {
+        previous_winner: winner.to_string(),
+    }];
+    let event = AuctionReopened {
+        auction_id,
+        previous_winner: winner.to_string(),
+    };
+
+    Ok(IbcBasicResponse::new()
+        .add_submessages(hook_msgs(deps.as_ref(), hooks)?)
+        .add_event(event.to_event())
+        .add_attribute("rolled_back", winner.to_string()))
+}
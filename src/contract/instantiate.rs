@@ -1,11 +1,35 @@
 use crate::error::BidError;
-use crate::msg::BidInstantiateMsg;
-use crate::state::{BID_CLOSED, BID_WINNER, DENOM, HIGHEST_BIDDER, OWNER};
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+use crate::merkle::decode_hash;
+use crate::msg::{
+    AcceptedAsset, AuctionItem, AuctionMetadata, BidInstantiateMsg, FeeRecipient, FeeShare,
+    StakingConfig,
+};
+use crate::royalties::{CheckRoyaltiesResponse, Cw2981QueryMsg, Cw721QueryMsg};
+use crate::state::{
+    EscrowTotal, ACCEPTED_ASSETS, ADMINS, ALLOWLIST_ONLY, AUCTION_ITEM, AWAITING_CONTROLLER,
+    BID_CLOSED, BID_WINNER, COMMISSION, COMMISSION_POLICY, COMMISSION_SPLIT, CONTROLLER_CHANNEL,
+    DENOM, ESCROW_TOTAL, EXPIRATION, FROZEN, GATE, HIGHEST_BIDDER, MAX_PAUSE_DURATION, MERKLE_ROOT,
+    METADATA, NOTIFY_WINNER, OPERATORS, ORACLE, OWNER, PAUSED_AT, PAY_ROYALTIES, PROCEEDS_SPLIT,
+    RELEASED, REMOTE_PROCEEDS_PENDING, REWARDS, STAKED, STAKING, UNBONDING, WINNER_CALLBACK_ERROR,
+};
+use cosmwasm_std::{Decimal, Deps, DepsMut, Env, MessageInfo, Response, Uint128};
+use std::collections::HashSet;
+
+// constant cut of 0.5 token by bid unless configured otherwise
+pub const DEFAULT_COMMISSION: u128 = 500_000u128;
+
+// metadata limits, in bytes for strings
+const MAX_TITLE: usize = 140;
+const MAX_DESCRIPTION: usize = 4_096;
+const MAX_URI: usize = 512;
+const MAX_ATTRIBUTES: usize = 32;
+const MAX_ATTRIBUTE_SIZE: usize = 256;
+const MAX_TAGS: usize = 16;
+const MAX_TAG: usize = 32;
 
 pub fn _instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: BidInstantiateMsg,
 ) -> Result<Response, BidError> {
@@ -16,9 +40,107 @@ pub fn _instantiate(
     };
 
     let denom = msg.denom.unwrap_or_else(|| "uatom".to_string());
+    let staking = msg
+        .staking
+        .map(|staking| validate_staking(deps.as_ref(), &denom, staking))
+        .transpose()?;
+    let accepted_assets = validate_assets(&denom, msg.accepted_assets.unwrap_or_default())?;
+    let oracle = msg
+        .oracle
+        .map(|oracle| deps.api.addr_validate(&oracle))
+        .transpose()?;
+    if !accepted_assets.is_empty() && oracle.is_none() {
+        return Err(BidError::InvalidAssets {
+            reason: "an oracle is required to price other denoms".to_string(),
+        });
+    }
+
+    let commission_split = validate_split(deps.as_ref(), msg.commission_split)?;
+    let proceeds_split = validate_split(deps.as_ref(), msg.proceeds_split)?;
+
+    let item = msg
+        .item
+        .map(|item| -> Result<_, BidError> {
+            Ok(AuctionItem {
+                contract: deps.api.addr_validate(&item.contract)?.to_string(),
+                token_id: item.token_id,
+            })
+        })
+        .transpose()?;
+
+    let pay_royalties = msg.pay_royalties.unwrap_or_default();
+    if pay_royalties {
+        let item = item.as_ref().ok_or(BidError::RoyaltiesWithoutItem)?;
+        let check: CheckRoyaltiesResponse = deps.querier.query_wasm_smart(
+            &item.contract,
+            &Cw721QueryMsg::Extension {
+                msg: Cw2981QueryMsg::CheckRoyalties {},
+            },
+        )?;
+
+        if !check.royalty_payments {
+            return Err(BidError::RoyaltiesNotSupported);
+        }
+    }
+
+    if let Some(expiration) = &msg.expiration {
+        if expiration.is_expired(&env.block) {
+            return Err(BidError::AuctionExpired);
+        }
+    }
+
+    let admins = msg.admins.unwrap_or_else(|| vec![owner.to_string()]);
+    if admins.is_empty() {
+        return Err(BidError::LastAdmin);
+    }
+    for admin in admins {
+        ADMINS.save(deps.storage, deps.api.addr_validate(&admin)?, &())?;
+    }
+    for operator in msg.operators.unwrap_or_default() {
+        OPERATORS.save(deps.storage, deps.api.addr_validate(&operator)?, &())?;
+    }
 
     OWNER.save(deps.storage, &owner)?;
     DENOM.save(deps.storage, &denom)?;
+    for asset in accepted_assets {
+        ACCEPTED_ASSETS.save(deps.storage, asset.denom, &asset.commission)?;
+    }
+    ORACLE.save(deps.storage, &oracle)?;
+    COMMISSION.save(
+        deps.storage,
+        &msg.commission
+            .unwrap_or_else(|| Uint128::from(DEFAULT_COMMISSION)),
+    )?;
+    COMMISSION_POLICY.save(deps.storage, &msg.commission_policy.unwrap_or_default())?;
+    COMMISSION_SPLIT.save(deps.storage, &commission_split)?;
+    PROCEEDS_SPLIT.save(deps.storage, &proceeds_split)?;
+    AUCTION_ITEM.save(deps.storage, &item)?;
+    PAY_ROYALTIES.save(deps.storage, &pay_royalties)?;
+    METADATA.save(deps.storage, &validate_metadata(msg.metadata)?)?;
+    STAKING.save(deps.storage, &staking)?;
+    STAKED.save(deps.storage, &Uint128::zero())?;
+    UNBONDING.save(deps.storage, &None)?;
+    REWARDS.save(deps.storage, &None)?;
+    RELEASED.save(deps.storage, &None)?;
+    ESCROW_TOTAL.save(deps.storage, &EscrowTotal::default())?;
+    EXPIRATION.save(deps.storage, &msg.expiration)?;
+    ALLOWLIST_ONLY.save(deps.storage, &msg.allowlist_only.unwrap_or_default())?;
+    MERKLE_ROOT.save(deps.storage, &validate_merkle_root(msg.merkle_root)?)?;
+    let controller_opens = msg.controller_opens.unwrap_or_default();
+    PAUSED_AT.save(deps.storage, &controller_opens.then_some(env.block.time))?;
+    FROZEN.save(deps.storage, &false)?;
+    CONTROLLER_CHANNEL.save(deps.storage, &None)?;
+    REMOTE_PROCEEDS_PENDING.save(deps.storage, &false)?;
+    AWAITING_CONTROLLER.save(deps.storage, &controller_opens)?;
+    NOTIFY_WINNER.save(deps.storage, &msg.notify_winner.unwrap_or_default())?;
+    WINNER_CALLBACK_ERROR.save(deps.storage, &None)?;
+    MAX_PAUSE_DURATION.save(deps.storage, &msg.max_pause_duration)?;
+    GATE.save(
+        deps.storage,
+        &msg.gate
+            .map(|gate| deps.api.addr_validate(&gate))
+            .transpose()?,
+    )?;
     HIGHEST_BIDDER.save(deps.storage, &None)?;
     BID_CLOSED.save(deps.storage, &false)?;
     BID_WINNER.save(deps.storage, &None)?;
@@ -27,3 +149,151 @@ pub fn _instantiate(
         .add_attribute("owner", owner)
         .add_attribute("method", "instantiate"))
 }
+
+pub fn validate_merkle_root(root: Option<String>) -> Result<Option<String>, BidError> {
+    root.map(|root| {
+        decode_hash(&root)
+            .map(hex::encode)
+            .map_err(|_| BidError::InvalidMerkleRoot)
+    })
+    .transpose()
+}
+
+pub fn validate_metadata(
+    metadata: Option<AuctionMetadata>,
+) -> Result<Option<AuctionMetadata>, BidError> {
+    let metadata = match metadata {
+        Some(metadata) => metadata,
+        None => return Ok(None),
+    };
+
+    let check = |field: &str, value: &Option<String>, max: usize| match value {
+        Some(value) if value.len() > max => Err(BidError::InvalidMetadata {
+            reason: format!("{} longer than {} bytes", field, max),
+        }),
+        _ => Ok(()),
+    };
+    check("title", &metadata.title, MAX_TITLE)?;
+    check("description", &metadata.description, MAX_DESCRIPTION)?;
+    check("image", &metadata.image, MAX_URI)?;
+    check("animation_url", &metadata.animation_url, MAX_URI)?;
+    check("external_url", &metadata.external_url, MAX_URI)?;
+
+    let attributes = metadata.attributes.as_deref().unwrap_or_default();
+    if attributes.len() > MAX_ATTRIBUTES {
+        return Err(BidError::InvalidMetadata {
+            reason: format!("more than {} attributes", MAX_ATTRIBUTES),
+        });
+    }
+    for attribute in attributes {
+        if attribute.trait_type.len() + attribute.value.len() > MAX_ATTRIBUTE_SIZE {
+            return Err(BidError::InvalidMetadata {
+                reason: format!("attribute longer than {} bytes", MAX_ATTRIBUTE_SIZE),
+            });
+        }
+    }
+
+    let tags = metadata.tags.as_deref().unwrap_or_default();
+    if tags.len() > MAX_TAGS {
+        return Err(BidError::InvalidMetadata {
+            reason: format!("more than {} tags", MAX_TAGS),
+        });
+    }
+    if tags.iter().any(|tag| tag.is_empty() || tag.len() > MAX_TAG) {
+        return Err(BidError::InvalidMetadata {
+            reason: format!("tags must be 1 to {} bytes", MAX_TAG),
+        });
+    }
+
+    Ok(Some(metadata))
+}
+
+// denoms accepted besides the quote one
+fn validate_assets(
+    quote: &str,
+    assets: Vec<AcceptedAsset>,
+) -> Result<Vec<AcceptedAsset>, BidError> {
+    let mut denoms = HashSet::new();
+    for asset in &assets {
+        if asset.denom.is_empty() {
+            return Err(BidError::InvalidAssets {
+                reason: "empty denom".to_string(),
+            });
+        }
+
+        if asset.denom == quote || !denoms.insert(asset.denom.as_str()) {
+            return Err(BidError::InvalidAssets {
+                reason: format!("{} accepted twice", asset.denom),
+            });
+        }
+    }
+
+    Ok(assets)
+}
+
+// bids are delegated as they come, so they must be in the staking denom
+fn validate_staking(
+    deps: Deps,
+    denom: &str,
+    staking: StakingConfig,
+) -> Result<StakingConfig, BidError> {
+    if deps.querier.query_bonded_denom()? != denom {
+        return Err(BidError::InvalidStaking {
+            reason: format!("{} is not the staking denom", denom),
+        });
+    }
+
+    if deps.querier.query_validator(&staking.validator)?.is_none() {
+        return Err(BidError::InvalidStaking {
+            reason: format!("unknown validator {}", staking.validator),
+        });
+    }
+
+    Ok(staking)
+}
+
+pub fn default_split() -> Vec<FeeShare> {
+    vec![FeeShare {
+        recipient: FeeRecipient::Owner {},
+        weight: Decimal::one(),
+    }]
+}
+
+// everything goes to the owner when no split is given
+pub fn validate_split(deps: Deps, split: Option<Vec<FeeShare>>) -> Result<Vec<FeeShare>, BidError> {
+    let split = match split {
+        Some(split) => split,
+        None => return Ok(default_split()),
+    };
+
+    if split.is_empty() || split.iter().any(|share| share.weight.is_zero()) {
+        return Err(BidError::InvalidFeeSplit);
+    }
+
+    let total = split
+        .iter()
+        .try_fold(Decimal::zero(), |total, share| {
+            total.checked_add(share.weight)
+        })
+        .map_err(|_| BidError::InvalidFeeSplit)?;
+    if total != Decimal::one() {
+        return Err(BidError::InvalidFeeSplit);
+    }
+
+    split
+        .into_iter()
+        .map(|share| {
+            let recipient = match share.recipient {
+                FeeRecipient::Address { address } => FeeRecipient::Address {
+                    address: deps.api.addr_validate(&address)?.to_string(),
+                },
+                recipient => recipient,
+            };
+
+            Ok(FeeShare {
+                recipient,
+                weight: share.weight,
+            })
+        })
+        .collect()
+}
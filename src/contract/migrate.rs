@@ -0,0 +1,97 @@
+use crate::contract::instantiate::{default_split, DEFAULT_COMMISSION};
+use crate::contract::{CONTRACT_NAME, CONTRACT_VERSION};
+use crate::error::BidError;
+use crate::msg::{BidMigrateMsg, CommissionPolicy};
+use crate::state::{
+    EscrowTotal, ADMINS, ALLOWLIST_ONLY, AUCTION_ITEM, AWAITING_CONTROLLER, BID_BY_ADDR,
+    BID_RETRACTED_FOR_ADDR, COMMISSION, COMMISSION_POLICY, COMMISSION_SPLIT, CONTROLLER_CHANNEL,
+    ESCROW_TOTAL, EXPIRATION, FROZEN, GATE, HIGHEST_BIDDER, LEADERS, MAX_PAUSE_DURATION,
+    MERKLE_ROOT, METADATA, NOTIFY_WINNER, ORACLE, OWNER, PAUSED_AT, PAY_ROYALTIES, PROCEEDS_SPLIT,
+    RELEASED, REMOTE_PROCEEDS_PENDING, REWARDS, STAKED, STAKING, UNBONDING, WINNER_CALLBACK_ERROR,
+};
+use cosmwasm_std::{DepsMut, Env, Order, Response, StdError, Storage, Uint128};
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
+
+pub fn _migrate(deps: DepsMut, _env: Env, _msg: BidMigrateMsg) -> Result<Response, BidError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(BidError::ForeignContract {
+            contract: stored.contract,
+        });
+    }
+
+    let from = parse_version(&stored.version)?;
+    let to = parse_version(CONTRACT_VERSION)?;
+    if from > to {
+        return Err(BidError::CannotDowngrade {
+            from: stored.version,
+            to: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    if from < Version::new(0, 1, 0) {
+        migrate_from_v0_0(deps.storage)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("method", "migrate"))
+}
+
+fn parse_version(version: &str) -> Result<Version, BidError> {
+    Version::parse(version).map_err(|err| StdError::generic_err(err.to_string()).into())
+}
+
+// 0.0.x only stored owner, denom and the bids, every other setting gets the
+// value instantiate gives it by default so running auctions keep their behaviour
+fn migrate_from_v0_0(storage: &mut dyn Storage) -> Result<(), BidError> {
+    let owner = OWNER.load(storage)?;
+
+    // the current leader heads the leaders bids placed from now on are stacked on
+    if let Some(leader) = HIGHEST_BIDDER.load(storage)? {
+        LEADERS.push_back(storage, &leader)?;
+    }
+
+    COMMISSION.save(storage, &Uint128::from(DEFAULT_COMMISSION))?;
+    COMMISSION_POLICY.save(storage, &CommissionPolicy::Kept)?;
+    COMMISSION_SPLIT.save(storage, &default_split())?;
+    PROCEEDS_SPLIT.save(storage, &default_split())?;
+    AUCTION_ITEM.save(storage, &None)?;
+    PAY_ROYALTIES.save(storage, &false)?;
+    ADMINS.save(storage, owner, &())?;
+    EXPIRATION.save(storage, &None)?;
+    ALLOWLIST_ONLY.save(storage, &false)?;
+    MERKLE_ROOT.save(storage, &None)?;
+    PAUSED_AT.save(storage, &None)?;
+    FROZEN.save(storage, &false)?;
+    CONTROLLER_CHANNEL.save(storage, &None)?;
+    REMOTE_PROCEEDS_PENDING.save(storage, &false)?;
+    AWAITING_CONTROLLER.save(storage, &false)?;
+    NOTIFY_WINNER.save(storage, &false)?;
+    WINNER_CALLBACK_ERROR.save(storage, &None)?;
+    MAX_PAUSE_DURATION.save(storage, &None)?;
+    GATE.save(storage, &None)?;
+    ORACLE.save(storage, &None)?;
+    METADATA.save(storage, &None)?;
+    STAKING.save(storage, &None)?;
+    STAKED.save(storage, &Uint128::zero())?;
+    UNBONDING.save(storage, &None)?;
+    REWARDS.save(storage, &None)?;
+    RELEASED.save(storage, &None)?;
+
+    // every 0.0.x bid was in the quote denom and kept its commission
+    let mut escrowed = EscrowTotal::default();
+    for bid in BID_BY_ADDR.range(storage, None, None, Order::Ascending) {
+        let (bidder, bid) = bid?;
+        if !BID_RETRACTED_FOR_ADDR.has(storage, bidder) {
+            escrowed.bid += bid;
+        }
+    }
+    ESCROW_TOTAL.save(storage, &escrowed)?;
+
+    Ok(())
+}
@@ -1,19 +1,90 @@
-use crate::msg::BidQueryMsg;
+use crate::contract::deposit::total_deposits;
+use crate::contract::execute::{bid_value, check_bid, ensure_funder};
+use crate::contract::staking::{escrows, stake_locked};
+use crate::msg::{
+    AcceptedAsset, BidAllowance, BidQueryMsg, IbcOrigin, ReconcileResponse, RolesResponse,
+    SimulateBidResponse, StakingResponse,
+};
 use crate::state::{
-    BID_BY_ADDR, BID_CLOSED, BID_WINNER, COMMISSION_BY_ADDR, DENOM, HIGHEST_BIDDER, OWNER,
+    auction_status, bid_denom, highest_bidder, ACCEPTED_ASSETS, ADMINS, ALLOWANCES, ALLOWLIST,
+    ALLOWLIST_ONLY, APPROVED_FUNDERS, AUCTION_ITEM, BID_BY_ADDR, BID_CLOSED, BID_EXPIRES_AT,
+    BID_WINNER, COMMISSION, COMMISSION_BY_ADDR, COMMISSION_POLICY, COMMISSION_SPLIT, DENOM,
+    DENYLIST, DEPOSITS, EXPIRATION, FUNDER_BY_ADDR, GATE, HOOKS, IBC_ORIGIN_BY_ADDR, MERKLE_ROOT,
+    METADATA, OPERATORS, ORACLE, OWNER, PAY_ROYALTIES, PENDING_OWNER, PROCEEDS_SPLIT,
+    REFERRER_BY_ADDR, RELEASED, REWARDS, STAKED, STAKING, UNBONDING, WINNER_CALLBACK_ERROR,
 };
-use cosmwasm_std::{to_binary, Binary, Deps, Env, StdError, StdResult, Uint128};
+use cosmwasm_std::{to_binary, Addr, Binary, Coin, Deps, Env, Order, StdError, StdResult, Uint128};
+use cw_storage_plus::{Bound, Map};
+use cw_utils::Expiration;
 use std::ops::AddAssign;
 
-pub fn _query(deps: Deps, _env: Env, msg: BidQueryMsg) -> StdResult<Binary> {
+// pagination of address lists
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+pub fn _query(deps: Deps, env: Env, msg: BidQueryMsg) -> StdResult<Binary> {
     match msg {
         BidQueryMsg::GetOwner {} => to_binary(&OWNER.load(deps.storage)?),
+        BidQueryMsg::Owner {} => to_binary(&OWNER.may_load(deps.storage)?),
+        BidQueryMsg::PendingOwner {} => to_binary(&PENDING_OWNER.may_load(deps.storage)?),
         BidQueryMsg::GetDenom {} => to_binary(&DENOM.load(deps.storage)?),
         BidQueryMsg::BidClosed {} => to_binary(&BID_CLOSED.load(deps.storage)?),
         BidQueryMsg::BidForAddress { address } => to_binary(&bid_for_address(deps, address)?),
-        BidQueryMsg::HighestBid {} => to_binary(&highest_bid(deps)?),
-        BidQueryMsg::HighestBidder {} => to_binary(&HIGHEST_BIDDER.load(deps.storage)?),
-        BidQueryMsg::BidWinner {} => to_binary(&BID_WINNER.load(deps.storage)?),
+        BidQueryMsg::HighestBid {} => to_binary(&highest_bid(deps, env)?),
+        BidQueryMsg::HighestBidder {} => to_binary(&highest_bidder(deps.storage, &env.block)?),
+        BidQueryMsg::BidWinner => to_binary(&BID_WINNER.load(deps.storage)?),
+        BidQueryMsg::GetCommissionPolicy {} => to_binary(&COMMISSION_POLICY.load(deps.storage)?),
+        BidQueryMsg::CommissionFor { address } => to_binary(&commission_for(deps, address)?),
+        BidQueryMsg::GetCommissionSplit {} => to_binary(&COMMISSION_SPLIT.load(deps.storage)?),
+        BidQueryMsg::GetProceedsSplit {} => to_binary(&PROCEEDS_SPLIT.load(deps.storage)?),
+        BidQueryMsg::ReferrerFor { address } => to_binary(&referrer_for(deps, address)?),
+        BidQueryMsg::GetItem {} => to_binary(&AUCTION_ITEM.load(deps.storage)?),
+        BidQueryMsg::PayRoyalties {} => to_binary(&PAY_ROYALTIES.load(deps.storage)?),
+        BidQueryMsg::Roles {} => to_binary(&roles(deps)?),
+        BidQueryMsg::GetExpiration {} => to_binary(&EXPIRATION.load(deps.storage)?),
+        BidQueryMsg::AllowlistOnly {} => to_binary(&ALLOWLIST_ONLY.load(deps.storage)?),
+        BidQueryMsg::Allowlist { start_after, limit } => {
+            to_binary(&list_addresses(deps, ALLOWLIST, start_after, limit)?)
+        }
+        BidQueryMsg::Denylist { start_after, limit } => {
+            to_binary(&list_addresses(deps, DENYLIST, start_after, limit)?)
+        }
+        BidQueryMsg::MerkleRoot {} => to_binary(&MERKLE_ROOT.load(deps.storage)?),
+        BidQueryMsg::Gate {} => to_binary(&GATE.load(deps.storage)?),
+        BidQueryMsg::Status {} => to_binary(&auction_status(deps.storage, &env.block)?),
+        BidQueryMsg::GetCommission {} => to_binary(&COMMISSION.load(deps.storage)?),
+        BidQueryMsg::IbcOriginFor { address } => to_binary(&ibc_origin_for(deps, address)?),
+        BidQueryMsg::WinnerCallbackError {} => {
+            to_binary(&WINNER_CALLBACK_ERROR.load(deps.storage)?)
+        }
+        BidQueryMsg::Reconcile { denom } => to_binary(&reconcile(deps, env, denom)?),
+        BidQueryMsg::AcceptedAssets {} => to_binary(&accepted_assets(deps)?),
+        BidQueryMsg::Oracle {} => to_binary(&ORACLE.load(deps.storage)?),
+        BidQueryMsg::BidDenomFor { address } => to_binary(&bid_denom_for(deps, address)?),
+        BidQueryMsg::BidExpiresAt { address } => to_binary(&bid_expires_at(deps, address)?),
+        BidQueryMsg::FunderFor { address } => to_binary(&funder_for(deps, address)?),
+        BidQueryMsg::FunderApproved { bidder, funder } => {
+            to_binary(&funder_approved(deps, bidder, funder)?)
+        }
+        BidQueryMsg::Deposits { address } => to_binary(&deposits(deps, address)?),
+        BidQueryMsg::Allowance { owner, agent } => to_binary(&allowance(deps, owner, agent)?),
+        BidQueryMsg::SimulateBid {
+            bidder,
+            funder,
+            funds,
+            referrer,
+            proof,
+            expires_at,
+        } => to_binary(&simulate_bid(
+            deps, env, bidder, funder, funds, referrer, proof, expires_at,
+        )?),
+        BidQueryMsg::Staking {} => to_binary(&staking(deps)?),
+        BidQueryMsg::Metadata {} => to_binary(&METADATA.load(deps.storage)?),
+        BidQueryMsg::Hooks {} => to_binary(
+            &HOOKS
+                .keys(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()?,
+        ),
     }
 }
 
@@ -21,8 +92,10 @@ fn bid_for_address(deps: Deps, address: String) -> StdResult<Uint128> {
     let validated_addr = deps.api.addr_validate(address.as_str())?;
 
     let bid = BID_BY_ADDR.load(deps.storage, validated_addr.clone());
+    // remote bids pay no commission here
     let comm = COMMISSION_BY_ADDR
-        .load(deps.storage, validated_addr)?
+        .may_load(deps.storage, validated_addr)?
+        .flatten()
         .unwrap_or_default();
 
     if let Ok(mut bid) = bid {
@@ -33,18 +106,260 @@ fn bid_for_address(deps: Deps, address: String) -> StdResult<Uint128> {
     }
 }
 
-fn highest_bid(deps: Deps) -> StdResult<Uint128> {
-    let addr = HIGHEST_BIDDER.load(deps.storage)?;
+fn commission_for(deps: Deps, address: String) -> StdResult<Uint128> {
+    let validated_addr = deps.api.addr_validate(address.as_str())?;
+
+    Ok(COMMISSION_BY_ADDR
+        .may_load(deps.storage, validated_addr)?
+        .flatten()
+        .unwrap_or_default())
+}
+
+fn accepted_assets(deps: Deps) -> StdResult<Vec<AcceptedAsset>> {
+    let mut assets = vec![AcceptedAsset {
+        denom: DENOM.load(deps.storage)?,
+        commission: COMMISSION.load(deps.storage)?,
+    }];
+    for asset in ACCEPTED_ASSETS.range(deps.storage, None, None, Order::Ascending) {
+        let (denom, commission) = asset?;
+        assets.push(AcceptedAsset { denom, commission });
+    }
+
+    Ok(assets)
+}
+
+fn bid_denom_for(deps: Deps, address: String) -> StdResult<Option<String>> {
+    let validated_addr = deps.api.addr_validate(address.as_str())?;
+    if !BID_BY_ADDR.has(deps.storage, validated_addr.clone()) {
+        return Ok(None);
+    }
+
+    bid_denom(deps.storage, &validated_addr).map(Some)
+}
+
+fn funder_for(deps: Deps, address: String) -> StdResult<Option<Addr>> {
+    let validated_addr = deps.api.addr_validate(address.as_str())?;
+
+    FUNDER_BY_ADDR.may_load(deps.storage, validated_addr)
+}
+
+fn funder_approved(deps: Deps, bidder: String, funder: String) -> StdResult<bool> {
+    let bidder = deps.api.addr_validate(bidder.as_str())?;
+    let funder = deps.api.addr_validate(funder.as_str())?;
+
+    Ok(APPROVED_FUNDERS.has(deps.storage, (bidder, funder)))
+}
+
+fn deposits(deps: Deps, address: String) -> StdResult<Vec<Coin>> {
+    let validated_addr = deps.api.addr_validate(address.as_str())?;
+
+    DEPOSITS
+        .prefix(validated_addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|deposit| deposit.map(|(denom, amount)| Coin { denom, amount }))
+        .collect()
+}
+
+fn allowance(deps: Deps, owner: String, agent: String) -> StdResult<Option<BidAllowance>> {
+    let owner = deps.api.addr_validate(owner.as_str())?;
+    let agent = deps.api.addr_validate(agent.as_str())?;
+
+    ALLOWANCES.may_load(deps.storage, (owner, agent))
+}
+
+fn bid_expires_at(deps: Deps, address: String) -> StdResult<Option<Expiration>> {
+    let validated_addr = deps.api.addr_validate(address.as_str())?;
+
+    BID_EXPIRES_AT.may_load(deps.storage, validated_addr)
+}
+
+// runs the checks of `bid`, a bidder or its wallet sees why a bid would fail before sending it
+#[allow(clippy::too_many_arguments)]
+fn simulate_bid(
+    deps: Deps,
+    env: Env,
+    bidder: String,
+    funder: Option<String>,
+    funds: Vec<Coin>,
+    referrer: Option<String>,
+    proof: Option<Vec<String>>,
+    expires_at: Option<Expiration>,
+) -> StdResult<SimulateBidResponse> {
+    let bidder = deps.api.addr_validate(&bidder)?;
+    let funder = match funder {
+        Some(funder) => deps.api.addr_validate(&funder)?,
+        None => bidder.clone(),
+    };
+
+    let checked = check_bid(
+        deps,
+        &env,
+        &bidder,
+        &funds,
+        referrer,
+        proof,
+        expires_at.as_ref(),
+    )
+    .and_then(|checked| {
+        ensure_funder(deps, &bidder, &funder, checked.current_bid)?;
+        Ok(checked)
+    });
+
+    Ok(match checked {
+        Ok(checked) => SimulateBidResponse {
+            error: None,
+            denom: Some(checked.denom),
+            total: checked.new_bid,
+            commission: checked.commission,
+            value: checked.value,
+            leads: true,
+            previous_leader: checked.highest_bidder,
+        },
+        Err(err) => SimulateBidResponse {
+            error: Some(err.to_string()),
+            denom: None,
+            total: Uint128::zero(),
+            commission: Uint128::zero(),
+            value: Uint128::zero(),
+            leads: false,
+            previous_leader: None,
+        },
+    })
+}
+
+// walks every bid, meant for audits and tests rather than contracts
+pub fn reconcile(deps: Deps, env: Env, denom: Option<String>) -> StdResult<ReconcileResponse> {
+    let quote = DENOM.load(deps.storage)?;
+    let denom = denom.unwrap_or_else(|| quote.clone());
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+
+    // only the quote denom is staked
+    let staked = if denom == quote {
+        STAKED.load(deps.storage)?
+            + UNBONDING
+                .load(deps.storage)?
+                .map(|unbonding| unbonding.amount)
+                .unwrap_or_default()
+    } else {
+        Uint128::zero()
+    };
+
+    // a staked winning bid is only paid once released
+    let locked = stake_locked(deps.storage)?;
+    let paid = if BID_CLOSED.load(deps.storage)? && !locked {
+        BID_WINNER.load(deps.storage)?
+    } else {
+        None
+    };
+
+    // a slashed stake is owed as what is left of it, the rewards as what is left of them
+    let (released, rewards) = if denom == quote {
+        (RELEASED.load(deps.storage)?, REWARDS.load(deps.storage)?)
+    } else {
+        (None, None)
+    };
+    let mut escrowed = Uint128::zero();
+    if released.is_none() {
+        for escrow in escrows(deps.storage)? {
+            if escrow.denom != denom || paid.as_ref() == Some(&escrow.bidder) {
+                continue;
+            }
+
+            escrowed += escrow.bid + escrow.commission;
+        }
+    }
+    for pool in released.iter().chain(rewards.iter()) {
+        escrowed += pool.amount;
+    }
+
+    // deposits are owed to their owners as bids are
+    escrowed += total_deposits(deps.storage, &denom)?;
+
+    let assets = balance + staked;
+    let excess = assets.saturating_sub(escrowed);
+    let unreleased_rewards = if locked && denom == quote {
+        excess
+    } else {
+        Uint128::zero()
+    };
+
+    Ok(ReconcileResponse {
+        denom,
+        balance,
+        staked,
+        escrowed,
+        unreleased_rewards,
+        surplus: excess - unreleased_rewards,
+        deficit: escrowed.saturating_sub(assets),
+    })
+}
+
+fn staking(deps: Deps) -> StdResult<StakingResponse> {
+    Ok(StakingResponse {
+        config: STAKING.load(deps.storage)?,
+        staked: STAKED.load(deps.storage)?,
+        unbonding: UNBONDING.load(deps.storage)?,
+        rewards: REWARDS.load(deps.storage)?,
+    })
+}
+
+fn referrer_for(deps: Deps, address: String) -> StdResult<Option<Addr>> {
+    let validated_addr = deps.api.addr_validate(address.as_str())?;
+
+    REFERRER_BY_ADDR.may_load(deps.storage, validated_addr)
+}
+
+fn ibc_origin_for(deps: Deps, address: String) -> StdResult<Option<IbcOrigin>> {
+    let validated_addr = deps.api.addr_validate(address.as_str())?;
+
+    IBC_ORIGIN_BY_ADDR.may_load(deps.storage, validated_addr)
+}
+
+fn roles(deps: Deps) -> StdResult<RolesResponse> {
+    Ok(RolesResponse {
+        admins: ADMINS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?,
+        operators: OPERATORS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?,
+    })
+}
+
+fn list_addresses(
+    deps: Deps,
+    list: Map<Addr, ()>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Addr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .map(Bound::exclusive);
+
+    list.keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+fn highest_bid(deps: Deps, env: Env) -> StdResult<Uint128> {
+    let addr = highest_bidder(deps.storage, &env.block)?;
 
     if let Some(highest_bidder) = addr {
         let bid = BID_BY_ADDR.load(deps.storage, highest_bidder.clone());
+        let denom = bid_denom(deps.storage, &highest_bidder)?;
         let comm = COMMISSION_BY_ADDR
-            .load(deps.storage, highest_bidder)?
+            .may_load(deps.storage, highest_bidder)?
+            .flatten()
             .unwrap_or_default();
 
         if let Ok(mut bid) = bid {
             bid.add_assign(&comm);
-            Ok(bid)
+            bid_value(deps, &denom, bid)
         } else {
             Err(StdError::generic_err("no bid present"))
         }
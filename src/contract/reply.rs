@@ -0,0 +1,47 @@
+use crate::error::BidError;
+use crate::ibc_hooks::transfer_sequence;
+use crate::state::{IBC_ORIGIN_BY_ADDR, IBC_REFUNDS, PENDING_IBC_REFUND, WINNER_CALLBACK_ERROR};
+use cosmwasm_std::{DepsMut, Env, Reply, Response, StdError};
+
+pub const HOOK_REPLY_ID: u64 = 1;
+pub const WINNER_CALLBACK_REPLY_ID: u64 = 2;
+pub const IBC_REFUND_REPLY_ID: u64 = 3;
+
+pub fn _reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, BidError> {
+    match msg.id {
+        // hooks are only replied to on error, swallowing it keeps the auction going
+        HOOK_REPLY_ID => Ok(Response::new()
+            .add_attribute("hook_error", msg.result.unwrap_err())
+            .add_attribute("method", "hook_failed")),
+        // the auction is settled anyway, the winner has to claim the item by other means
+        WINNER_CALLBACK_REPLY_ID => {
+            let err = msg.result.unwrap_err();
+            WINNER_CALLBACK_ERROR.save(deps.storage, &Some(err.clone()))?;
+
+            Ok(Response::new()
+                .add_attribute("winner_callback_error", err)
+                .add_attribute("method", "winner_callback_failed"))
+        }
+        IBC_REFUND_REPLY_ID => {
+            let refund = PENDING_IBC_REFUND.load(deps.storage)?;
+            PENDING_IBC_REFUND.remove(deps.storage);
+
+            let data = msg
+                .result
+                .into_result()
+                .map_err(StdError::generic_err)?
+                .data
+                .ok_or_else(|| StdError::generic_err("missing MsgTransferResponse"))?;
+            let sequence = transfer_sequence(&data)?;
+            let channel = IBC_ORIGIN_BY_ADDR
+                .load(deps.storage, refund.bidder.clone())?
+                .channel;
+            IBC_REFUNDS.save(deps.storage, (channel, sequence), &refund)?;
+
+            Ok(Response::new()
+                .add_attribute("sequence", sequence.to_string())
+                .add_attribute("method", "ibc_refund_sent"))
+        }
+        id => Err(StdError::generic_err(format!("Unknown reply id {}", id)).into()),
+    }
+}
@@ -0,0 +1,359 @@
+use crate::contract::deposit::total_deposits;
+use crate::contract::execute::pay_winner;
+use crate::error::BidError;
+use crate::msg::{AuctionStatus, CommissionPolicy, RewardPool, RewardsRecipient, Unbonding};
+use crate::state::{
+    auction_status, bid_denom, pending_proceeds, refund_shares, EscrowTotal, BID_BY_ADDR,
+    BID_CLOSED, BID_RETRACTED_FOR_ADDR, BID_WINNER, COMMISSION_BY_ADDR, COMMISSION_POLICY, DENOM,
+    ESCROW_TOTAL, OWNER, RELEASED, REMOTE_BIDDER_BY_ADDR, REWARDS, STAKED, STAKING, UNBONDING,
+};
+use cosmwasm_std::{
+    coin, coins, Addr, BankMsg, CosmosMsg, DepsMut, DistributionMsg, Env, Order, QuerierWrapper,
+    Response, StakingMsg, StdResult, Storage, Uint128,
+};
+
+// the chain may take longer to unbond than configured, a shortfall left after it is a slash
+pub const SLASH_GRACE: u64 = 86_400;
+
+// what the contract still owes a local bidder, remote bids are escrowed on the controller chain
+pub struct Escrow {
+    pub bidder: Addr,
+    pub denom: String,
+    pub bid: Uint128,
+    pub commission: Uint128,
+}
+
+pub fn escrow(storage: &dyn Storage, bidder: Addr, bid: Uint128) -> StdResult<Escrow> {
+    let refundable = COMMISSION_POLICY.load(storage)? == CommissionPolicy::Refundable;
+
+    let commission = if refundable {
+        COMMISSION_BY_ADDR
+            .may_load(storage, bidder.clone())?
+            .flatten()
+            .unwrap_or_default()
+    } else {
+        Uint128::zero()
+    };
+
+    Ok(Escrow {
+        denom: bid_denom(storage, &bidder)?,
+        bidder,
+        bid,
+        commission,
+    })
+}
+
+// walks every bid, only for queries
+pub fn escrows(storage: &dyn Storage) -> StdResult<Vec<Escrow>> {
+    let mut escrows = vec![];
+    for bid in BID_BY_ADDR.range(storage, None, None, Order::Ascending) {
+        let (bidder, bid) = bid?;
+        if BID_RETRACTED_FOR_ADDR.has(storage, bidder.clone())
+            || REMOTE_BIDDER_BY_ADDR.has(storage, bidder.clone())
+        {
+            continue;
+        }
+
+        escrows.push(escrow(storage, bidder, bid)?);
+    }
+
+    Ok(escrows)
+}
+
+// a local escrow entering or leaving, only the quote denom is staked
+pub fn add_escrow(storage: &mut dyn Storage, escrow: &Escrow) -> StdResult<()> {
+    if escrow.denom != DENOM.load(storage)? {
+        return Ok(());
+    }
+
+    ESCROW_TOTAL.update(storage, |total| -> StdResult<_> {
+        Ok(EscrowTotal {
+            bid: total.bid + escrow.bid,
+            commission: total.commission + escrow.commission,
+        })
+    })?;
+
+    Ok(())
+}
+
+pub fn remove_escrow(storage: &mut dyn Storage, escrow: &Escrow) -> StdResult<()> {
+    if escrow.denom != DENOM.load(storage)? {
+        return Ok(());
+    }
+
+    ESCROW_TOTAL.update(storage, |total| -> StdResult<_> {
+        Ok(EscrowTotal {
+            bid: total.bid - escrow.bid,
+            commission: total.commission - escrow.commission,
+        })
+    })?;
+
+    Ok(())
+}
+
+// what the local escrows in the quote denom still owe, from the running totals
+pub fn owed_escrows(storage: &dyn Storage) -> StdResult<Uint128> {
+    let escrowed = match RELEASED.load(storage)? {
+        Some(released) => released.amount,
+        None => {
+            let total = ESCROW_TOTAL.load(storage)?;
+            total.bid + total.commission
+        }
+    };
+    let rewards = REWARDS
+        .load(storage)?
+        .map(|pool| pool.amount)
+        .unwrap_or_default();
+
+    Ok(escrowed + rewards + total_deposits(storage, &DENOM.load(storage)?)?)
+}
+
+// the pro-rata part of `amount` in a pool, taken out of it
+fn take_share(pool: &mut RewardPool, amount: Uint128) -> Uint128 {
+    if pool.total.is_zero() {
+        return Uint128::zero();
+    }
+
+    let share = pool.amount.multiply_ratio(amount, pool.total);
+    pool.amount -= share;
+    pool.total = pool.total.saturating_sub(amount);
+    share
+}
+
+// staking rewards owed to a local bidder leaving once the stake is released, only the
+// quote denom is staked
+pub fn take_reward_share(
+    storage: &mut dyn Storage,
+    bidder: &Addr,
+    denom: &str,
+    bid: Uint128,
+) -> StdResult<Uint128> {
+    if denom != DENOM.load(storage)? || REMOTE_BIDDER_BY_ADDR.has(storage, bidder.clone()) {
+        return Ok(Uint128::zero());
+    }
+
+    let mut pool = match REWARDS.load(storage)? {
+        Some(pool) => pool,
+        None => return Ok(Uint128::zero()),
+    };
+    let share = take_share(&mut pool, bid);
+    REWARDS.save(storage, &Some(pool))?;
+
+    Ok(share)
+}
+
+// what is left of a local staked escrow leaving once released, remote and deposited
+// funds were never staked
+pub fn take_released(
+    storage: &mut dyn Storage,
+    bidder: &Addr,
+    denom: &str,
+    amount: Uint128,
+) -> StdResult<Uint128> {
+    if denom != DENOM.load(storage)? || REMOTE_BIDDER_BY_ADDR.has(storage, bidder.clone()) {
+        return Ok(amount);
+    }
+
+    let mut pool = match RELEASED.load(storage)? {
+        Some(pool) => pool,
+        None => return Ok(amount),
+    };
+    let share = take_share(&mut pool, amount);
+    RELEASED.save(storage, &Some(pool))?;
+
+    Ok(share)
+}
+
+// the escrow is out of reach while delegated or unbonding
+pub fn stake_locked(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(!STAKED.load(storage)?.is_zero() || UNBONDING.load(storage)?.is_some())
+}
+
+pub fn delegate_msgs(storage: &mut dyn Storage, amount: Uint128) -> StdResult<Vec<CosmosMsg>> {
+    let config = match STAKING.load(storage)? {
+        Some(config) if !amount.is_zero() => config,
+        _ => return Ok(vec![]),
+    };
+
+    STAKED.update(storage, |staked| -> StdResult<_> { Ok(staked + amount) })?;
+
+    Ok(vec![CosmosMsg::Staking(StakingMsg::Delegate {
+        validator: config.validator,
+        amount: coin(amount.u128(), DENOM.load(storage)?),
+    })])
+}
+
+// rewards are withdrawn first, the delegation is gone once undelegated
+pub fn unstake_msgs(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+) -> StdResult<Vec<CosmosMsg>> {
+    let config = match STAKING.load(storage)? {
+        Some(config) => config,
+        None => return Ok(vec![]),
+    };
+    let staked = STAKED.load(storage)?;
+    if staked.is_zero() {
+        return Ok(vec![]);
+    }
+
+    // a slash leaves less delegated than was staked
+    let delegation = querier.query_delegation(&env.contract.address, &config.validator)?;
+    let delegated = delegation
+        .as_ref()
+        .map(|delegation| delegation.amount.amount)
+        .unwrap_or_default()
+        .min(staked);
+    let has_rewards = delegation
+        .map(|delegation| {
+            delegation
+                .accumulated_rewards
+                .iter()
+                .any(|reward| !reward.amount.is_zero())
+        })
+        .unwrap_or_default();
+
+    STAKED.save(storage, &Uint128::zero())?;
+    UNBONDING.save(
+        storage,
+        &Some(Unbonding {
+            amount: delegated,
+            until: env.block.time.plus_seconds(config.unbonding_period),
+        }),
+    )?;
+    if delegated.is_zero() {
+        return Ok(vec![]);
+    }
+
+    let mut msgs = vec![];
+    if has_rewards {
+        msgs.push(CosmosMsg::Distribution(
+            DistributionMsg::WithdrawDelegatorReward {
+                validator: config.validator.clone(),
+            },
+        ));
+    }
+    msgs.push(CosmosMsg::Staking(StakingMsg::Undelegate {
+        validator: config.validator,
+        amount: coin(delegated.u128(), DENOM.load(storage)?),
+    }));
+
+    Ok(msgs)
+}
+
+pub fn unstake(deps: DepsMut, env: Env) -> Result<Response, BidError> {
+    match auction_status(deps.storage, &env.block)? {
+        AuctionStatus::Closed => {}
+        AuctionStatus::Cancelled => {
+            // persist a cancellation caused by a pause running too long
+            BID_CLOSED.save(deps.storage, &true)?;
+            BID_WINNER.save(deps.storage, &None)?;
+        }
+        _ => return Err(BidError::BidNotClosed),
+    }
+
+    if STAKED.load(deps.storage)?.is_zero() {
+        return Err(BidError::NothingStaked);
+    }
+    let msgs = unstake_msgs(deps.storage, &deps.querier, &env)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("method", "unstake"))
+}
+
+// everything above what is owed to bidders once unbonded is rewards
+pub fn release_stake(deps: DepsMut, env: Env) -> Result<Response, BidError> {
+    let unbonding = UNBONDING
+        .load(deps.storage)?
+        .ok_or(BidError::NothingToRelease)?;
+    if env.block.time < unbonding.until {
+        return Err(BidError::StillUnbonding);
+    }
+    let config = STAKING
+        .load(deps.storage)?
+        .ok_or(BidError::NothingToRelease)?;
+
+    // the chain may take longer to unbond than configured
+    let denom = DENOM.load(deps.storage)?;
+    let escrowed = ESCROW_TOTAL.load(deps.storage)?;
+    // deposits are not staked, they are owed as they are
+    let staked = escrowed.bid + escrowed.commission;
+    let deposits = total_deposits(deps.storage, &denom)?;
+    // the escrow of a remote winner is owed to the seller, it arrives long before the
+    // unbonding ends
+    let proceeds = pending_proceeds(deps.storage)?;
+    let due = staked + deposits + proceeds;
+
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+    let mut released = staked;
+    if balance < due {
+        // past the grace a shortfall is a slash, every staked escrow bears its share
+        if env.block.time < unbonding.until.plus_seconds(SLASH_GRACE) {
+            return Err(BidError::StillUnbonding);
+        }
+        released = balance.saturating_sub(deposits + proceeds);
+        RELEASED.save(
+            deps.storage,
+            &Some(RewardPool {
+                amount: released,
+                total: staked,
+            }),
+        )?;
+    }
+    let rewards = balance.saturating_sub(deposits + proceeds + released);
+
+    // rewards nobody bid for go to the seller
+    let shared = match config.rewards_to {
+        RewardsRecipient::Bidders if !escrowed.bid.is_zero() => rewards,
+        _ => Uint128::zero(),
+    };
+    UNBONDING.save(deps.storage, &None)?;
+    REWARDS.save(
+        deps.storage,
+        &Some(RewardPool {
+            amount: shared,
+            total: escrowed.bid,
+        }),
+    )?;
+    let seller_rewards = rewards - shared;
+
+    let mut response = Response::new();
+    let winner = BID_WINNER
+        .load(deps.storage)?
+        .filter(|winner| !REMOTE_BIDDER_BY_ADDR.has(deps.storage, winner.clone()));
+    if let Some(winner) = winner {
+        let bid = BID_BY_ADDR.load(deps.storage, winner.clone())?;
+        let escrow = escrow(deps.storage, winner.clone(), bid)?;
+        remove_escrow(deps.storage, &escrow)?;
+        let winning_bid = take_released(deps.storage, &winner, &escrow.denom, escrow.bid)?;
+        let commission = take_released(deps.storage, &winner, &escrow.denom, escrow.commission)?;
+        let share = take_reward_share(deps.storage, &winner, &escrow.denom, bid)?;
+        response = pay_winner(deps.as_ref(), &env, &winner, winning_bid, commission)?;
+
+        for (to, share) in refund_shares(deps.storage, &winner, share)? {
+            response = response.add_message(BankMsg::Send {
+                to_address: to.to_string(),
+                amount: coins(share.u128(), &denom),
+            });
+        }
+    }
+
+    if let Some(owner) = OWNER.may_load(deps.storage)? {
+        if !seller_rewards.is_zero() {
+            response = response.add_message(BankMsg::Send {
+                to_address: owner.to_string(),
+                amount: coins(seller_rewards.u128(), &denom),
+            });
+        }
+    }
+
+    Ok(response
+        .add_attribute("rewards", rewards.to_string())
+        .add_attribute("seller_rewards", seller_rewards.to_string())
+        .add_attribute("method", "release_stake"))
+}
@@ -0,0 +1,116 @@
+use crate::contract::deposit::credit_deposit;
+use crate::contract::execute::{cancel_auction, settle};
+use crate::contract::instantiate::validate_split;
+use crate::contract::staking::{add_escrow, escrow};
+use crate::error::BidError;
+use crate::msg::{AuctionStatus, BidSudoMsg, FeeShare, IbcLifecycleComplete};
+use crate::state::{
+    status_ignoring_freeze, BID_BY_ADDR, BID_CLOSED, BID_RETRACTED_FOR_ADDR, COMMISSION,
+    COMMISSION_SPLIT, FROZEN, IBC_REFUNDS,
+};
+use cosmwasm_std::{DepsMut, Env, Response, Uint128};
+
+pub fn _sudo(deps: DepsMut, env: Env, msg: BidSudoMsg) -> Result<Response, BidError> {
+    match msg {
+        BidSudoMsg::ForceClose {} => force_close(deps, env),
+        BidSudoMsg::ForceCancel {} => {
+            Ok(cancel_auction(deps, env)?.add_attribute("method", "force_cancel"))
+        }
+        BidSudoMsg::Freeze {} => freeze(deps),
+        BidSudoMsg::Unfreeze {} => unfreeze(deps),
+        BidSudoMsg::UpdateCommission {
+            commission,
+            commission_split,
+        } => update_commission(deps, commission, commission_split),
+        BidSudoMsg::IbcLifecycleComplete(lifecycle) => ibc_lifecycle_complete(deps, lifecycle),
+    }
+}
+
+// settles even a paused or frozen auction, the freeze stays until lifted
+fn force_close(deps: DepsMut, env: Env) -> Result<Response, BidError> {
+    if BID_CLOSED.load(deps.storage)? {
+        return Err(BidError::BidAlreadyClosed);
+    }
+
+    // a pause that ran past its max duration already cancelled the auction
+    if status_ignoring_freeze(deps.storage, &env.block)? == AuctionStatus::Cancelled {
+        return Err(BidError::AuctionCancelled);
+    }
+
+    Ok(settle(deps, env)?.add_attribute("method", "force_close"))
+}
+
+fn freeze(deps: DepsMut) -> Result<Response, BidError> {
+    if FROZEN.load(deps.storage)? {
+        return Err(BidError::Frozen);
+    }
+
+    FROZEN.save(deps.storage, &true)?;
+
+    Ok(Response::new().add_attribute("method", "freeze"))
+}
+
+fn unfreeze(deps: DepsMut) -> Result<Response, BidError> {
+    if !FROZEN.load(deps.storage)? {
+        return Err(BidError::NotFrozen);
+    }
+
+    FROZEN.save(deps.storage, &false)?;
+
+    Ok(Response::new().add_attribute("method", "unfreeze"))
+}
+
+fn update_commission(
+    deps: DepsMut,
+    commission: Option<Uint128>,
+    commission_split: Option<Vec<FeeShare>>,
+) -> Result<Response, BidError> {
+    if let Some(commission) = commission {
+        COMMISSION.save(deps.storage, &commission)?;
+    }
+
+    if let Some(split) = commission_split {
+        let split = validate_split(deps.as_ref(), Some(split))?;
+        COMMISSION_SPLIT.save(deps.storage, &split)?;
+    }
+
+    Ok(Response::new().add_attribute("method", "update_commission"))
+}
+
+// the ICS-20 module has already returned the funds of a failed refund to the contract
+fn ibc_lifecycle_complete(
+    deps: DepsMut,
+    lifecycle: IbcLifecycleComplete,
+) -> Result<Response, BidError> {
+    let (channel, sequence, success) = match lifecycle {
+        IbcLifecycleComplete::IbcAck {
+            channel,
+            sequence,
+            success,
+            ..
+        } => (channel, sequence, success),
+        IbcLifecycleComplete::IbcTimeout { channel, sequence } => (channel, sequence, false),
+    };
+
+    let key = (channel, sequence);
+    let refund = IBC_REFUNDS.load(deps.storage, key.clone())?;
+    IBC_REFUNDS.remove(deps.storage, key);
+
+    if !success {
+        match &refund.withdrawn {
+            // the withdrawn bid is gone, the funds are kept as a deposit of its payer
+            Some(withdrawn) => credit_deposit(deps.storage, &refund.recipient, withdrawn)?,
+            None => {
+                BID_RETRACTED_FOR_ADDR.remove(deps.storage, refund.bidder.clone());
+                let bid = BID_BY_ADDR.load(deps.storage, refund.bidder.clone())?;
+                let escrow = escrow(deps.storage, refund.bidder.clone(), bid)?;
+                add_escrow(deps.storage, &escrow)?;
+            }
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("bidder", refund.bidder)
+        .add_attribute("refunded", success.to_string())
+        .add_attribute("method", "ibc_lifecycle_complete"))
+}
@@ -30,6 +30,9 @@ pub enum BidError {
     #[error("RetractAlreadyDone")]
     RetractAlreadyDone,
 
+    #[error("Bid Expired")]
+    BidExpired,
+
     #[error("Only Owner Can Close")]
     OnlyOwnerCanClose,
 
@@ -38,4 +41,163 @@ pub enum BidError {
 
     #[error("Invalid Highest Bidder")]
     InvalidHighestBidder,
+
+    #[error("Invalid Fee Split")]
+    InvalidFeeSplit,
+
+    #[error("Cannot Refer Yourself")]
+    SelfReferral,
+
+    #[error("Only The Funder Of A Bid Can Raise It")]
+    FunderMismatch,
+
+    #[error("The Bidder Has Not Approved This Funder")]
+    FunderNotApproved,
+
+    #[error("Only The Bidder Or Its Funder Can Retract While The Auction Runs")]
+    OnlyBidderCanRetract,
+
+    #[error("Denom {denom} Is Not Accepted")]
+    UnacceptedDenom { denom: String },
+
+    #[error("Nothing Deposited")]
+    NothingDeposited,
+
+    #[error("Insufficient Deposit")]
+    InsufficientDeposit,
+
+    #[error("Invalid Allowance: {reason}")]
+    InvalidAllowance { reason: String },
+
+    #[error("No Allowance")]
+    NoAllowance,
+
+    #[error("Allowance Expired")]
+    AllowanceExpired,
+
+    #[error("Allowance Exceeded")]
+    AllowanceExceeded,
+
+    #[error("Royalties Need An Item")]
+    RoyaltiesWithoutItem,
+
+    #[error("Item Does Not Pay Royalties")]
+    RoyaltiesNotSupported,
+
+    #[error("Only Owner")]
+    OnlyOwner,
+
+    #[error("No Pending Owner")]
+    NoPendingOwner,
+
+    #[error("Only Pending Owner")]
+    OnlyPendingOwner,
+
+    #[error("Ownership Proposal Expired")]
+    OwnershipProposalExpired,
+
+    #[error("Only Admin")]
+    OnlyAdmin,
+
+    #[error("Cannot Remove Last Admin")]
+    LastAdmin,
+
+    #[error("Auction Expired")]
+    AuctionExpired,
+
+    #[error("Auction Not Expired")]
+    AuctionNotExpired,
+
+    #[error("Not Whitelisted")]
+    NotWhitelisted,
+
+    #[error("Banned")]
+    Banned,
+
+    #[error("Invalid Merkle Root")]
+    InvalidMerkleRoot,
+
+    #[error("Invalid Merkle Proof")]
+    InvalidMerkleProof,
+
+    #[error("Rejected By Gate")]
+    RejectedByGate,
+
+    #[error("Paused")]
+    Paused,
+
+    #[error("Not Paused")]
+    NotPaused,
+
+    #[error("Not Awaiting Controller")]
+    NotAwaitingController,
+
+    #[error("Auction Cancelled")]
+    AuctionCancelled,
+
+    #[error("Auction Frozen")]
+    Frozen,
+
+    #[error("Auction Not Frozen")]
+    NotFrozen,
+
+    #[error("Sender Is Not The IBC Hooks Account Of The Origin")]
+    InvalidIbcOrigin,
+
+    #[error("Invalid IBC Channel: {reason}")]
+    InvalidChannel { reason: String },
+
+    #[error("Remote Bids Are Retracted Through The Controller")]
+    RemoteBid,
+
+    #[error("No Remote Proceeds To Collect")]
+    NoProceedsPending,
+
+    #[error("Remote Proceeds Not Arrived Yet")]
+    ProceedsNotArrived,
+
+    #[error("Remote Proceeds Are Still To Be Paid Out")]
+    ProceedsPending,
+
+    #[error("Hook Already Registered")]
+    HookAlreadyRegistered,
+
+    #[error("Hook Not Registered")]
+    HookNotRegistered,
+
+    #[error("Cannot Migrate From {contract}")]
+    ForeignContract { contract: String },
+
+    #[error("Cannot Downgrade From {from} To {to}")]
+    CannotDowngrade { from: String, to: String },
+
+    #[error("Invalid Metadata: {reason}")]
+    InvalidMetadata { reason: String },
+
+    #[error("Metadata Cannot Change Once Bids Are Placed")]
+    MetadataLocked,
+
+    #[error("Invalid Staking: {reason}")]
+    InvalidStaking { reason: String },
+
+    #[error("Escrow Is Staked Until Released")]
+    StakeLocked,
+
+    #[error("Nothing Staked")]
+    NothingStaked,
+
+    #[error("Nothing To Release")]
+    NothingToRelease,
+
+    #[error("Still Unbonding")]
+    StillUnbonding,
+
+    #[error("Invalid Accepted Assets: {reason}")]
+    InvalidAssets { reason: String },
+
+    #[error("Bid In A Single Denom")]
+    MultipleDenoms,
+
+    #[error("Bids Must Stay In The Denom Of The First One")]
+    DenomMismatch,
 }
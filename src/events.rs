@@ -0,0 +1,198 @@
+// typed events of the auction, shared by the contract, indexers and tests.
+// The chain prefixes the types with `wasm-`, so `bid_placed` is indexed as
+// `wasm-bid_placed`. Optional keys are left out when they have no value, as
+// the chain rejects empty attribute values.
+use cosmwasm_std::{Event, Uint128};
+
+pub const BID_PLACED: &str = "bid_placed";
+pub const AUCTION_CLOSED: &str = "auction_closed";
+pub const BID_RETRACTED: &str = "bid_retracted";
+pub const AUCTION_REOPENED: &str = "auction_reopened";
+
+// address of the auction contract
+pub const AUCTION_ID: &str = "auction_id";
+pub const BIDDER: &str = "bidder";
+// denom the amounts are in
+pub const DENOM: &str = "denom";
+// amount added by this bid, commission excluded
+pub const INCREMENT: &str = "increment";
+// total bid of the bidder after this bid
+pub const NEW_TOTAL: &str = "new_total";
+// leader before this bid, the bidder itself when raising its own lead,
+// missing for the first bid
+pub const PREVIOUS_LEADER: &str = "previous_leader";
+pub const COMMISSION: &str = "commission";
+// missing when the auction was cancelled
+pub const WINNER: &str = "winner";
+pub const AMOUNT: &str = "amount";
+pub const RECIPIENT: &str = "recipient";
+// missing when the bidder funded its own bid
+pub const FUNDER: &str = "funder";
+// remote winner whose settlement failed
+pub const PREVIOUS_WINNER: &str = "previous_winner";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BidPlaced {
+    pub auction_id: String,
+    pub bidder: String,
+    pub denom: String,
+    pub increment: Uint128,
+    pub new_total: Uint128,
+    pub previous_leader: Option<String>,
+    pub commission: Uint128,
+    pub funder: Option<String>,
+}
+
+// `amount` is the winning bid, `commission` the held commission paid with it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuctionClosed {
+    pub auction_id: String,
+    pub winner: Option<String>,
+    pub denom: String,
+    pub amount: Uint128,
+    pub commission: Uint128,
+}
+
+// `amount` is the refunded bid, `commission` the held commission refunded with it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BidRetracted {
+    pub auction_id: String,
+    pub bidder: String,
+    pub recipient: String,
+    pub denom: String,
+    pub amount: Uint128,
+    pub commission: Uint128,
+}
+
+// the settlement of a remote winner failed, the auction takes bids again
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuctionReopened {
+    pub auction_id: String,
+    pub previous_winner: String,
+}
+
+impl BidPlaced {
+    pub fn to_event(&self) -> Event {
+        let event = Event::new(BID_PLACED)
+            .add_attribute(AUCTION_ID, &self.auction_id)
+            .add_attribute(BIDDER, &self.bidder)
+            .add_attribute(DENOM, &self.denom)
+            .add_attribute(INCREMENT, self.increment)
+            .add_attribute(NEW_TOTAL, self.new_total)
+            .add_attribute(COMMISSION, self.commission);
+        let event = add_optional(event, PREVIOUS_LEADER, &self.previous_leader);
+        add_optional(event, FUNDER, &self.funder)
+    }
+
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if !is_type(event, BID_PLACED) {
+            return None;
+        }
+
+        Some(Self {
+            auction_id: attribute(event, AUCTION_ID)?,
+            bidder: attribute(event, BIDDER)?,
+            denom: attribute(event, DENOM)?,
+            increment: amount(event, INCREMENT)?,
+            new_total: amount(event, NEW_TOTAL)?,
+            previous_leader: attribute(event, PREVIOUS_LEADER),
+            commission: amount(event, COMMISSION)?,
+            funder: attribute(event, FUNDER),
+        })
+    }
+}
+
+impl AuctionClosed {
+    pub fn to_event(&self) -> Event {
+        let event = Event::new(AUCTION_CLOSED)
+            .add_attribute(AUCTION_ID, &self.auction_id)
+            .add_attribute(DENOM, &self.denom)
+            .add_attribute(AMOUNT, self.amount)
+            .add_attribute(COMMISSION, self.commission);
+        add_optional(event, WINNER, &self.winner)
+    }
+
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if !is_type(event, AUCTION_CLOSED) {
+            return None;
+        }
+
+        Some(Self {
+            auction_id: attribute(event, AUCTION_ID)?,
+            winner: attribute(event, WINNER),
+            denom: attribute(event, DENOM)?,
+            amount: amount(event, AMOUNT)?,
+            commission: amount(event, COMMISSION)?,
+        })
+    }
+}
+
+impl BidRetracted {
+    pub fn to_event(&self) -> Event {
+        Event::new(BID_RETRACTED)
+            .add_attribute(AUCTION_ID, &self.auction_id)
+            .add_attribute(BIDDER, &self.bidder)
+            .add_attribute(RECIPIENT, &self.recipient)
+            .add_attribute(DENOM, &self.denom)
+            .add_attribute(AMOUNT, self.amount)
+            .add_attribute(COMMISSION, self.commission)
+    }
+
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if !is_type(event, BID_RETRACTED) {
+            return None;
+        }
+
+        Some(Self {
+            auction_id: attribute(event, AUCTION_ID)?,
+            bidder: attribute(event, BIDDER)?,
+            recipient: attribute(event, RECIPIENT)?,
+            denom: attribute(event, DENOM)?,
+            amount: amount(event, AMOUNT)?,
+            commission: amount(event, COMMISSION)?,
+        })
+    }
+}
+
+impl AuctionReopened {
+    pub fn to_event(&self) -> Event {
+        Event::new(AUCTION_REOPENED)
+            .add_attribute(AUCTION_ID, &self.auction_id)
+            .add_attribute(PREVIOUS_WINNER, &self.previous_winner)
+    }
+
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if !is_type(event, AUCTION_REOPENED) {
+            return None;
+        }
+
+        Some(Self {
+            auction_id: attribute(event, AUCTION_ID)?,
+            previous_winner: attribute(event, PREVIOUS_WINNER)?,
+        })
+    }
+}
+
+// accepts the type as emitted by the contract and as indexed by the chain
+fn is_type(event: &Event, ty: &str) -> bool {
+    event.ty == ty || event.ty.strip_prefix("wasm-") == Some(ty)
+}
+
+fn attribute(event: &Event, key: &str) -> Option<String> {
+    event
+        .attributes
+        .iter()
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.clone())
+}
+
+fn add_optional(event: Event, key: &str, value: &Option<String>) -> Event {
+    match value {
+        Some(value) => event.add_attribute(key, value),
+        None => event,
+    }
+}
+
+fn amount(event: &Event, key: &str) -> Option<Uint128> {
+    attribute(event, key)?.parse().ok()
+}
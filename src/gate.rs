@@ -0,0 +1,9 @@
+// query interface of the external compliance registry gating bids and payouts
+use cosmwasm_schema::{cw_serde, QueryResponses};
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum GateQueryMsg {
+    #[returns(bool)]
+    IsAllowed { address: String },
+}
@@ -0,0 +1,43 @@
+// interface of the contracts subscribed to auction events, each event is sent
+// as `{"auction_hook": {...}}` and a failing subscriber never fails the auction.
+// A winner that is a contract can also be told it won, like cw20 `Receive`.
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Uint128;
+
+#[cw_serde]
+pub enum AuctionHookMsg {
+    AuctionHook(AuctionHook),
+}
+
+#[cw_serde]
+pub enum AuctionHook {
+    // `amount` is the new total bid of `bidder`
+    BidPlaced {
+        bidder: String,
+        amount: Uint128,
+    },
+    // `bidder` lost the lead it had with `amount`
+    Outbid {
+        bidder: String,
+        amount: Uint128,
+    },
+    // no winner when the auction was cancelled
+    Closed {
+        winner: Option<String>,
+        amount: Uint128,
+    },
+    Retracted {
+        bidder: String,
+        amount: Uint128,
+    },
+    // the settlement of the remote winner failed, bids are taken again
+    Reopened {
+        previous_winner: String,
+    },
+}
+
+#[cw_serde]
+pub enum WinnerCallbackMsg {
+    // `auction_id` is the address of the auction, `amount` the winning bid
+    ReceiveAuctionWin { auction_id: String, amount: Uint128 },
+}
@@ -0,0 +1,108 @@
+// ibc-hooks executes the wasm memo of an ICS-20 transfer from an intermediate
+// account derived from the channel on this chain and the sender on the origin
+// chain, so a cross chain bidder is only known here by that account.
+use cosmwasm_std::{
+    Addr, Api, Binary, CanonicalAddr, Coin, CosmosMsg, StdError, StdResult, Timestamp,
+};
+use sha2::{Digest, Sha256};
+
+pub const SENDER_PREFIX: &str = "ibc-wasm-hook-intermediary";
+
+// same derivation as the ibc-hooks module: sha256(sha256(prefix) || "channel/sender")
+pub fn intermediate_sender(api: &dyn Api, channel: &str, original_sender: &str) -> StdResult<Addr> {
+    let hash = Sha256::new()
+        .chain_update(Sha256::digest(SENDER_PREFIX.as_bytes()))
+        .chain_update(format!("{}/{}", channel, original_sender).as_bytes())
+        .finalize();
+
+    api.addr_humanize(&CanonicalAddr::from(hash.to_vec()))
+}
+
+pub const TRANSFER_TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransfer";
+
+// ICS-20 transfer asking ibc-hooks to call the sender back with its ack or timeout,
+// IbcMsg::Transfer has no memo so the MsgTransfer is encoded by hand
+pub fn transfer_with_callback(
+    channel: &str,
+    sender: &Addr,
+    receiver: &str,
+    amount: &Coin,
+    timeout: Timestamp,
+) -> CosmosMsg {
+    let mut token = vec![];
+    encode_bytes(&mut token, 1, amount.denom.as_bytes());
+    encode_bytes(&mut token, 2, amount.amount.to_string().as_bytes());
+
+    let memo = format!(r#"{{"ibc_callback":"{}"}}"#, sender);
+    let mut value = vec![];
+    encode_bytes(&mut value, 1, b"transfer");
+    encode_bytes(&mut value, 2, channel.as_bytes());
+    encode_bytes(&mut value, 3, &token);
+    encode_bytes(&mut value, 4, sender.as_bytes());
+    encode_bytes(&mut value, 5, receiver.as_bytes());
+    encode_varint(&mut value, 7 << 3);
+    encode_varint(&mut value, timeout.nanos());
+    encode_bytes(&mut value, 8, memo.as_bytes());
+
+    CosmosMsg::Stargate {
+        type_url: TRANSFER_TYPE_URL.to_string(),
+        value: Binary(value),
+    }
+}
+
+// packet sequence of a MsgTransferResponse, the key of the ibc-hooks callback
+pub fn transfer_sequence(mut data: &[u8]) -> StdResult<u64> {
+    while !data.is_empty() {
+        let key = decode_varint(&mut data)?;
+        match key & 7 {
+            0 => {
+                let value = decode_varint(&mut data)?;
+                if key >> 3 == 1 {
+                    return Ok(value);
+                }
+            }
+            2 => {
+                let len = decode_varint(&mut data)? as usize;
+                data = data.get(len..).ok_or_else(truncated)?;
+            }
+            _ => return Err(truncated()),
+        }
+    }
+
+    Err(StdError::parse_err(
+        "MsgTransferResponse",
+        "missing sequence",
+    ))
+}
+
+fn encode_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    encode_varint(buf, field << 3 | 2);
+    encode_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn decode_varint(data: &mut &[u8]) -> StdResult<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (byte, rest) = data.split_first().ok_or_else(truncated)?;
+        *data = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(truncated())
+}
+
+fn truncated() -> StdError {
+    StdError::parse_err("MsgTransferResponse", "invalid encoding")
+}
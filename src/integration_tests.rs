@@ -1,11 +1,51 @@
 #[cfg(test)]
 mod integration {
-    use crate::contract::{execute, instantiate, query};
+    use crate::contract::{
+        execute, ibc_channel_connect, ibc_channel_open, ibc_packet_ack, ibc_packet_receive,
+        ibc_packet_timeout, instantiate, migrate, query, reply, sudo,
+    };
     use crate::error::BidError;
     use crate::error::BidError::{BidWinner, NoBidPresent};
-    use crate::msg::{BidExecuteMsg, BidInstantiateMsg, BidQueryMsg};
-    use cosmwasm_std::{coin, coins, Addr, Coin, Empty, StdResult, Uint128};
-    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+    use crate::events::{AuctionClosed, AuctionReopened, BidPlaced, BidRetracted};
+    use crate::gate::GateQueryMsg;
+    use crate::hooks::{AuctionHook, AuctionHookMsg};
+    use crate::ibc_hooks::{intermediate_sender, transfer_with_callback};
+    use crate::merkle::MerkleTree;
+    use crate::msg::{
+        AcceptedAsset, AuctionItem, AuctionMetadata, AuctionStatus, BidAllowance, BidExecuteMsg,
+        BidInstantiateMsg, BidMigrateMsg, BidQueryMsg, BidSudoMsg, CommissionPolicy, FeeRecipient,
+        FeeShare, IbcLifecycleComplete, IbcOrigin, MetadataAttribute, PendingOwnership,
+        ReconcileResponse, RewardsRecipient, Role, RolesResponse, SimulateBidResponse,
+        StakingConfig, StakingResponse,
+    };
+    use crate::oracle::{OracleQueryMsg, PriceResponse};
+    use crate::packet::{AuctionPacket, ControllerPacket, PacketAck, IBC_VERSION};
+    use crate::royalties::{
+        CheckRoyaltiesResponse, Cw2981QueryMsg, Cw721QueryMsg, RoyaltiesInfoResponse,
+    };
+    use crate::state::{
+        BID_BY_ADDR, BID_CLOSED, BID_WINNER, COMMISSION_BY_ADDR, DENOM, HIGHEST_BIDDER, OWNER,
+    };
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_ibc_channel_connect_ack, mock_ibc_channel_open_try,
+        mock_ibc_packet_ack, mock_ibc_packet_recv, mock_ibc_packet_timeout, mock_info, MockApi,
+        MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        coin, coins, from_binary, to_binary, Addr, Api, BankMsg, Binary, CanonicalAddr, Coin,
+        CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, FullDelegation, IbcAcknowledgement, IbcMsg,
+        IbcOrder, IbcTimeout, MessageInfo, OwnedDeps, RecoverPubkeyError, Reply, ReplyOn, Response,
+        StdError, StdResult, SubMsgResponse, SubMsgResult, Uint128, Validator, VerificationError,
+        WasmMsg,
+    };
+    use cw2::{query_contract_info, set_contract_version};
+    use cw_multi_test::{
+        App, AppResponse, Contract, ContractWrapper, Executor, StakingInfo, StakingSudo, SudoMsg,
+    };
+    use cw_storage_plus::{Item, Map};
+    use cw_utils::Expiration;
+    use std::marker::PhantomData;
 
     pub struct BidContract(Addr);
 
@@ -19,16 +59,30 @@ mod integration {
             owner: Option<String>,
             denom: Option<String>,
         ) -> StdResult<BidContract> {
-            app.instantiate_contract(
+            BidContract::instantiate_with_msg(
+                app,
                 code_id,
-                sender.clone(),
-                &BidInstantiateMsg { owner, denom },
-                &[],
+                sender,
                 label,
-                None,
+                BidInstantiateMsg {
+                    owner,
+                    denom,
+                    ..Default::default()
+                },
             )
-            .map_err(|err| err.downcast().unwrap())
-            .map(BidContract)
+        }
+
+        #[track_caller]
+        pub fn instantiate_with_msg(
+            app: &mut App,
+            code_id: u64,
+            sender: &Addr,
+            label: String,
+            msg: BidInstantiateMsg,
+        ) -> StdResult<BidContract> {
+            app.instantiate_contract(code_id, sender.clone(), &msg, &[], label, None)
+                .map_err(|err| err.downcast().unwrap())
+                .map(BidContract)
         }
 
         #[track_caller]
@@ -43,6 +97,24 @@ mod integration {
                 .query_wasm_smart(self.0.clone(), &BidQueryMsg::GetOwner {})
         }
 
+        #[track_caller]
+        pub fn pending_owner(&self, app: &App) -> StdResult<Option<PendingOwnership>> {
+            app.wrap()
+                .query_wasm_smart(self.0.clone(), &BidQueryMsg::PendingOwner {})
+        }
+
+        #[track_caller]
+        pub fn roles(&self, app: &App) -> StdResult<RolesResponse> {
+            app.wrap()
+                .query_wasm_smart(self.0.clone(), &BidQueryMsg::Roles {})
+        }
+
+        #[track_caller]
+        pub fn status(&self, app: &App) -> StdResult<AuctionStatus> {
+            app.wrap()
+                .query_wasm_smart(self.0.clone(), &BidQueryMsg::Status {})
+        }
+
         #[track_caller]
         pub fn closed(&self, app: &App) -> StdResult<bool> {
             app.wrap()
@@ -68,29 +140,98 @@ mod integration {
         }
 
         #[track_caller]
-        pub fn bid(&self, app: &mut App, sender: &Addr, funds: &[Coin]) -> Result<(), BidError> {
-            app.execute_contract(
-                sender.clone(),
+        pub fn commission_policy(&self, app: &App) -> StdResult<CommissionPolicy> {
+            app.wrap()
+                .query_wasm_smart(self.0.clone(), &BidQueryMsg::GetCommissionPolicy {})
+        }
+
+        #[track_caller]
+        pub fn commission_for(&self, app: &App, address: &Addr) -> StdResult<Uint128> {
+            app.wrap().query_wasm_smart(
                 self.0.clone(),
-                &BidExecuteMsg::Bid {},
-                funds,
+                &BidQueryMsg::CommissionFor {
+                    address: address.to_string(),
+                },
             )
-            .map_err(|err| err.downcast::<BidError>().unwrap())?;
-            Ok(())
         }
 
         #[track_caller]
-        pub fn close(&self, app: &mut App, sender: &Addr, funds: &[Coin]) -> Result<(), BidError> {
-            app.execute_contract(
-                sender.clone(),
+        pub fn referrer_for(&self, app: &App, address: &Addr) -> StdResult<Option<Addr>> {
+            app.wrap().query_wasm_smart(
                 self.0.clone(),
-                &BidExecuteMsg::Close {},
+                &BidQueryMsg::ReferrerFor {
+                    address: address.to_string(),
+                },
+            )
+        }
+
+        #[track_caller]
+        pub fn bid(&self, app: &mut App, sender: &Addr, funds: &[Coin]) -> Result<(), BidError> {
+            self.execute(
+                app,
+                sender,
+                &BidExecuteMsg::Bid {
+                    referrer: None,
+                    proof: None,
+                    ibc_origin: None,
+                    expires_at: None,
+                    on_behalf_of: None,
+                },
                 funds,
             )
-            .map_err(|err| err.downcast::<BidError>().unwrap())?;
+        }
+
+        #[track_caller]
+        pub fn execute(
+            &self,
+            app: &mut App,
+            sender: &Addr,
+            msg: &BidExecuteMsg,
+            funds: &[Coin],
+        ) -> Result<(), BidError> {
+            self.execute_with_response(app, sender, msg, funds)?;
+            Ok(())
+        }
+
+        #[track_caller]
+        pub fn execute_with_response(
+            &self,
+            app: &mut App,
+            sender: &Addr,
+            msg: &BidExecuteMsg,
+            funds: &[Coin],
+        ) -> Result<AppResponse, BidError> {
+            self.execute_in_deficit(app, sender, msg, funds, Uint128::zero())
+        }
+
+        // a slash shows as a deficit of the quote denom until the stake is released
+        #[track_caller]
+        pub fn execute_in_deficit(
+            &self,
+            app: &mut App,
+            sender: &Addr,
+            msg: &BidExecuteMsg,
+            funds: &[Coin],
+            deficit: Uint128,
+        ) -> Result<AppResponse, BidError> {
+            let res = app.execute_contract(sender.clone(), self.0.clone(), msg, funds);
+            self.assert_deficit(app, deficit);
+            res.map_err(|err| err.downcast::<BidError>().unwrap())
+        }
+
+        #[track_caller]
+        pub fn sudo(&self, app: &mut App, msg: &BidSudoMsg) -> Result<(), BidError> {
+            let res = app.wasm_sudo(self.0.clone(), msg);
+            self.assert_reconciled(app);
+            res.map_err(|err| err.downcast::<BidError>().unwrap())?;
             Ok(())
         }
 
+        #[track_caller]
+        pub fn close(&self, app: &mut App, sender: &Addr, funds: &[Coin]) -> Result<(), BidError> {
+            self.execute(app, sender, &BidExecuteMsg::Close {}, funds)
+        }
+
         #[track_caller]
         pub fn retract(
             &self,
@@ -99,19 +240,374 @@ mod integration {
             funds: &[Coin],
             receiver: Option<String>,
         ) -> Result<(), BidError> {
-            app.execute_contract(
-                sender.clone(),
-                self.0.clone(),
-                &BidExecuteMsg::Retract { receiver },
-                funds,
-            )
-            .map_err(|err| err.downcast::<BidError>().unwrap())?;
-            Ok(())
+            self.execute(app, sender, &BidExecuteMsg::Retract { receiver }, funds)
+        }
+
+        // escrow invariant of every accepted denom, checked after every step made through these helpers
+        #[track_caller]
+        pub fn assert_reconciled(&self, app: &App) {
+            self.assert_deficit(app, Uint128::zero());
+        }
+
+        // the quote denom comes first in the accepted assets
+        #[track_caller]
+        fn assert_deficit(&self, app: &App, deficit: Uint128) {
+            let assets: Vec<AcceptedAsset> = app
+                .wrap()
+                .query_wasm_smart(self.0.clone(), &BidQueryMsg::AcceptedAssets {})
+                .unwrap();
+            for (i, asset) in assets.into_iter().enumerate() {
+                let expected = if i == 0 { deficit } else { Uint128::zero() };
+                let reconcile: ReconcileResponse = app
+                    .wrap()
+                    .query_wasm_smart(
+                        self.0.clone(),
+                        &BidQueryMsg::Reconcile {
+                            denom: Some(asset.denom),
+                        },
+                    )
+                    .unwrap();
+                assert_eq!(
+                    (reconcile.surplus, reconcile.deficit),
+                    (Uint128::zero(), expected),
+                    "escrow drift: {:?}",
+                    reconcile
+                );
+            }
         }
     }
 
     fn bid_contract() -> Box<dyn Contract<Empty>> {
-        let contract = ContractWrapper::new(execute, instantiate, query);
+        let contract = ContractWrapper::new(execute, instantiate, query)
+            .with_migrate(migrate)
+            .with_sudo(sudo)
+            .with_reply(reply);
+
+        Box::new(contract)
+    }
+
+    // cw721 paying 10% royalties to "artist"
+    fn royalty_nft_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+                Ok(Response::new())
+            },
+            |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+                Ok(Response::new())
+            },
+            |_deps: Deps, _env: Env, msg: Cw721QueryMsg| -> StdResult<Binary> {
+                match msg {
+                    Cw721QueryMsg::Extension {
+                        msg: Cw2981QueryMsg::RoyaltyInfo { sale_price, .. },
+                    } => to_binary(&RoyaltiesInfoResponse {
+                        address: "artist".to_string(),
+                        royalty_amount: sale_price * Decimal::percent(10),
+                    }),
+                    Cw721QueryMsg::Extension {
+                        msg: Cw2981QueryMsg::CheckRoyalties {},
+                    } => to_binary(&CheckRoyaltiesResponse {
+                        royalty_payments: true,
+                    }),
+                }
+            },
+        );
+
+        Box::new(contract)
+    }
+
+    // MockApi that also renders the 32 byte ibc-hooks accounts, as "ibc1<hex>"
+    #[derive(Default)]
+    struct HookApi(MockApi);
+
+    impl Api for HookApi {
+        fn addr_validate(&self, input: &str) -> StdResult<Addr> {
+            self.addr_humanize(&self.addr_canonicalize(input)?)
+        }
+
+        fn addr_canonicalize(&self, input: &str) -> StdResult<CanonicalAddr> {
+            match input.strip_prefix("ibc1") {
+                Some(hash) => hex::decode(hash)
+                    .map(CanonicalAddr::from)
+                    .map_err(|err| StdError::generic_err(err.to_string())),
+                None => self.0.addr_canonicalize(input),
+            }
+        }
+
+        fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+            if canonical.len() == 32 {
+                Ok(Addr::unchecked(format!(
+                    "ibc1{}",
+                    hex::encode(canonical.as_slice())
+                )))
+            } else {
+                self.0.addr_humanize(canonical)
+            }
+        }
+
+        fn secp256k1_verify(
+            &self,
+            message_hash: &[u8],
+            signature: &[u8],
+            public_key: &[u8],
+        ) -> Result<bool, VerificationError> {
+            self.0.secp256k1_verify(message_hash, signature, public_key)
+        }
+
+        fn secp256k1_recover_pubkey(
+            &self,
+            message_hash: &[u8],
+            signature: &[u8],
+            recovery_param: u8,
+        ) -> Result<Vec<u8>, RecoverPubkeyError> {
+            self.0
+                .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+        }
+
+        fn ed25519_verify(
+            &self,
+            message: &[u8],
+            signature: &[u8],
+            public_key: &[u8],
+        ) -> Result<bool, VerificationError> {
+            self.0.ed25519_verify(message, signature, public_key)
+        }
+
+        fn ed25519_batch_verify(
+            &self,
+            messages: &[&[u8]],
+            signatures: &[&[u8]],
+            public_keys: &[&[u8]],
+        ) -> Result<bool, VerificationError> {
+            self.0
+                .ed25519_batch_verify(messages, signatures, public_keys)
+        }
+
+        fn debug(&self, message: &str) {
+            self.0.debug(message)
+        }
+    }
+
+    const MOCK_HOOKS_RECEIVED: Item<Vec<AuctionHook>> = Item::new("received");
+    const MOCK_HOOK_FAILS: Item<bool> = Item::new("fails");
+
+    // subscriber keeping every event, or failing on all of them when instantiated with true
+    fn hook_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            |deps: DepsMut,
+             _env: Env,
+             _info: MessageInfo,
+             msg: AuctionHookMsg|
+             -> StdResult<Response> {
+                if MOCK_HOOK_FAILS.load(deps.storage)? {
+                    return Err(StdError::generic_err("hook down"));
+                }
+
+                let AuctionHookMsg::AuctionHook(hook) = msg;
+                let mut received = MOCK_HOOKS_RECEIVED
+                    .may_load(deps.storage)?
+                    .unwrap_or_default();
+                received.push(hook);
+                MOCK_HOOKS_RECEIVED.save(deps.storage, &received)?;
+                Ok(Response::new())
+            },
+            |deps: DepsMut, _env: Env, _info: MessageInfo, fails: bool| -> StdResult<Response> {
+                MOCK_HOOK_FAILS.save(deps.storage, &fails)?;
+                Ok(Response::new())
+            },
+            |deps: Deps, _env: Env, _msg: Empty| -> StdResult<Binary> {
+                to_binary(
+                    &MOCK_HOOKS_RECEIVED
+                        .may_load(deps.storage)?
+                        .unwrap_or_default(),
+                )
+            },
+        );
+
+        Box::new(contract)
+    }
+
+    #[cw_serde]
+    enum MockVaultExecuteMsg {
+        Bid { auction: String },
+        ReceiveAuctionWin { auction_id: String, amount: Uint128 },
+    }
+
+    const MOCK_VAULT_WON: Item<Vec<(String, Uint128)>> = Item::new("won");
+
+    // dao vault bidding with the funds it is sent, failing on wins when instantiated with true
+    fn vault_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            |deps: DepsMut,
+             _env: Env,
+             info: MessageInfo,
+             msg: MockVaultExecuteMsg|
+             -> StdResult<Response> {
+                match msg {
+                    MockVaultExecuteMsg::Bid { auction } => {
+                        Ok(Response::new().add_message(WasmMsg::Execute {
+                            contract_addr: auction,
+                            msg: to_binary(&BidExecuteMsg::Bid {
+                                referrer: None,
+                                proof: None,
+                                ibc_origin: None,
+                                expires_at: None,
+                                on_behalf_of: None,
+                            })?,
+                            funds: info.funds,
+                        }))
+                    }
+                    MockVaultExecuteMsg::ReceiveAuctionWin { auction_id, amount } => {
+                        if MOCK_HOOK_FAILS.load(deps.storage)? {
+                            return Err(StdError::generic_err("vault locked"));
+                        }
+
+                        let mut won = MOCK_VAULT_WON.may_load(deps.storage)?.unwrap_or_default();
+                        won.push((auction_id, amount));
+                        MOCK_VAULT_WON.save(deps.storage, &won)?;
+                        Ok(Response::new())
+                    }
+                }
+            },
+            |deps: DepsMut, _env: Env, _info: MessageInfo, fails: bool| -> StdResult<Response> {
+                MOCK_HOOK_FAILS.save(deps.storage, &fails)?;
+                Ok(Response::new())
+            },
+            |deps: Deps, _env: Env, _msg: Empty| -> StdResult<Binary> {
+                to_binary(&MOCK_VAULT_WON.may_load(deps.storage)?.unwrap_or_default())
+            },
+        );
+
+        Box::new(contract)
+    }
+
+    #[cw_serde]
+    struct LegacyInstantiateMsg {
+        contract: String,
+        version: String,
+    }
+
+    // 0.0.1 storage layout, only bidding is supported
+    fn legacy_bid_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            |deps: DepsMut,
+             _env: Env,
+             info: MessageInfo,
+             _msg: BidExecuteMsg|
+             -> StdResult<Response> {
+                let commission = Uint128::new(500_000u128);
+                let new_bid = info.funds[0].amount - commission
+                    + BID_BY_ADDR
+                        .may_load(deps.storage, info.sender.clone())?
+                        .unwrap_or_default();
+                let paid = COMMISSION_BY_ADDR
+                    .may_load(deps.storage, info.sender.clone())?
+                    .flatten()
+                    .unwrap_or_default();
+
+                COMMISSION_BY_ADDR.save(
+                    deps.storage,
+                    info.sender.clone(),
+                    &Some(paid + commission),
+                )?;
+                HIGHEST_BIDDER.save(deps.storage, &Some(info.sender.clone()))?;
+                BID_BY_ADDR.save(deps.storage, info.sender, &new_bid)?;
+
+                Ok(Response::new().add_message(BankMsg::Send {
+                    to_address: OWNER.load(deps.storage)?.to_string(),
+                    amount: coins(commission.u128(), DENOM.load(deps.storage)?),
+                }))
+            },
+            |deps: DepsMut,
+             _env: Env,
+             info: MessageInfo,
+             msg: LegacyInstantiateMsg|
+             -> StdResult<Response> {
+                set_contract_version(deps.storage, msg.contract, msg.version)?;
+                OWNER.save(deps.storage, &info.sender)?;
+                DENOM.save(deps.storage, &"uatom".to_string())?;
+                HIGHEST_BIDDER.save(deps.storage, &None)?;
+                BID_CLOSED.save(deps.storage, &false)?;
+                BID_WINNER.save(deps.storage, &None)?;
+                Ok(Response::new())
+            },
+            query,
+        );
+
+        Box::new(contract)
+    }
+
+    #[cw_serde]
+    enum MockGateExecuteMsg {
+        SetAllowed { address: String, allowed: bool },
+    }
+
+    const MOCK_GATE_ALLOWED: Map<&str, bool> = Map::new("allowed");
+
+    // compliance registry allowing what was explicitly set
+    fn gate_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            |deps: DepsMut,
+             _env: Env,
+             _info: MessageInfo,
+             msg: MockGateExecuteMsg|
+             -> StdResult<Response> {
+                match msg {
+                    MockGateExecuteMsg::SetAllowed { address, allowed } => {
+                        MOCK_GATE_ALLOWED.save(deps.storage, &address, &allowed)?;
+                        Ok(Response::new())
+                    }
+                }
+            },
+            |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+                Ok(Response::new())
+            },
+            |deps: Deps, _env: Env, msg: GateQueryMsg| -> StdResult<Binary> {
+                match msg {
+                    GateQueryMsg::IsAllowed { address } => to_binary(
+                        &MOCK_GATE_ALLOWED
+                            .may_load(deps.storage, &address)?
+                            .unwrap_or_default(),
+                    ),
+                }
+            },
+        );
+
+        Box::new(contract)
+    }
+
+    #[cw_serde]
+    enum MockOracleExecuteMsg {
+        SetPrice { denom: String, rate: Decimal },
+    }
+
+    const MOCK_ORACLE_PRICES: Map<&str, Decimal> = Map::new("prices");
+
+    // price feed quoting what was explicitly set
+    fn oracle_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            |deps: DepsMut,
+             _env: Env,
+             _info: MessageInfo,
+             msg: MockOracleExecuteMsg|
+             -> StdResult<Response> {
+                match msg {
+                    MockOracleExecuteMsg::SetPrice { denom, rate } => {
+                        MOCK_ORACLE_PRICES.save(deps.storage, &denom, &rate)?;
+                        Ok(Response::new())
+                    }
+                }
+            },
+            |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+                Ok(Response::new())
+            },
+            |deps: Deps, _env: Env, msg: OracleQueryMsg| -> StdResult<Binary> {
+                match msg {
+                    OracleQueryMsg::Price { denom } => to_binary(&PriceResponse {
+                        rate: MOCK_ORACLE_PRICES.load(deps.storage, &denom)?,
+                    }),
+                }
+            },
+        );
 
         Box::new(contract)
     }
@@ -304,6 +800,13 @@ mod integration {
         assert_eq!(bid.highest_bidder(&app)?, Some(alex.clone()));
         assert_eq!(bid.highest_bid(&app)?, Uint128::new(20_000_000u128));
 
+        assert_eq!(
+            bid.commission_for(&app, &alex)?,
+            Uint128::new(1_000_000u128)
+        );
+        assert_eq!(bid.commission_for(&app, &ann)?, Uint128::new(1_000_000u128));
+        assert_eq!(bid.commission_for(&app, &owner)?, Uint128::zero());
+
         assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()),);
         assert_eq!(bid.highest_bidder(&app)?, Some(alex.clone()));
         assert_eq!(bid.highest_bid(&app)?, Uint128::new(20_000_000u128));
@@ -349,4 +852,3729 @@ mod integration {
 
         Ok(())
     }
+
+    #[test]
+    fn refundable_commission() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alex, coins(100_000_000u128, "uatom"))
+                .unwrap();
+
+            router
+                .bank
+                .init_balance(storage, &ann, coins(100_000_000u128, "uatom"))
+                .unwrap();
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                commission_policy: Some(CommissionPolicy::Refundable),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(bid.commission_policy(&app)?, CommissionPolicy::Refundable);
+
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(15_000_000, "uatom".to_string())),
+            Ok(()),
+        );
+        assert_eq!(
+            bid.bid(&mut app, &ann, &coins(17_000_000, "uatom".to_string())),
+            Ok(()),
+        );
+
+        // commissions are held by the contract
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?,
+            coin(0u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(bid.0.clone(), "uatom")?,
+            coin(32_000_000u128, "uatom")
+        );
+        assert_eq!(bid.commission_for(&app, &alex)?, Uint128::new(500_000u128));
+
+        // winner bid and commission go to the owner
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?,
+            coin(17_000_000u128, "uatom")
+        );
+
+        // loser gets the bid and the commission back
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(alex.clone(), "uatom")?,
+            coin(100_000_000u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(bid.0.clone(), "uatom")?,
+            coin(0u128, "uatom")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fee_split() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+        let referrer = Addr::unchecked("referrer");
+        let treasury = Addr::unchecked("treasury");
+
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alex, coins(100_000_000u128, "uatom"))
+                .unwrap();
+
+            router
+                .bank
+                .init_balance(storage, &ann, coins(100_000_000u128, "uatom"))
+                .unwrap();
+        });
+
+        let contract_id = app.store_code(bid_contract());
+
+        let err = app
+            .instantiate_contract(
+                contract_id,
+                owner.clone(),
+                &BidInstantiateMsg {
+                    commission_split: Some(vec![FeeShare {
+                        recipient: FeeRecipient::Owner {},
+                        weight: Decimal::percent(90),
+                    }]),
+                    ..Default::default()
+                },
+                &[],
+                "label",
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<BidError>().unwrap(),
+            BidError::InvalidFeeSplit
+        );
+
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                commission_split: Some(vec![
+                    FeeShare {
+                        recipient: FeeRecipient::Owner {},
+                        weight: Decimal::percent(50),
+                    },
+                    FeeShare {
+                        recipient: FeeRecipient::Referrer {},
+                        weight: Decimal::percent(30),
+                    },
+                    FeeShare {
+                        recipient: FeeRecipient::Address {
+                            address: treasury.to_string(),
+                        },
+                        weight: Decimal::percent(20),
+                    },
+                ]),
+                proceeds_split: Some(vec![
+                    FeeShare {
+                        recipient: FeeRecipient::Owner {},
+                        weight: Decimal::percent(90),
+                    },
+                    FeeShare {
+                        recipient: FeeRecipient::Address {
+                            address: treasury.to_string(),
+                        },
+                        weight: Decimal::percent(10),
+                    },
+                ]),
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &alex,
+                &BidExecuteMsg::Bid {
+                    referrer: Some(alex.to_string()),
+                    proof: None,
+                    ibc_origin: None,
+                    expires_at: None,
+                    on_behalf_of: None,
+                },
+                &coins(10_000_000, "uatom"),
+            ),
+            Err(BidError::SelfReferral)
+        );
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &alex,
+                &BidExecuteMsg::Bid {
+                    referrer: Some(referrer.to_string()),
+                    proof: None,
+                    ibc_origin: None,
+                    expires_at: None,
+                    on_behalf_of: None,
+                },
+                &coins(10_000_000, "uatom"),
+            ),
+            Ok(())
+        );
+        assert_eq!(bid.referrer_for(&app, &alex)?, Some(referrer.clone()));
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?,
+            coin(250_000u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(referrer.clone(), "uatom")?,
+            coin(150_000u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(treasury.clone(), "uatom")?,
+            coin(100_000u128, "uatom")
+        );
+
+        // without referrer its share goes to the owner
+        assert_eq!(bid.bid(&mut app, &ann, &coins(12_000_000, "uatom")), Ok(()));
+        assert_eq!(bid.referrer_for(&app, &ann)?, None);
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?,
+            coin(650_000u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(treasury.clone(), "uatom")?,
+            coin(200_000u128, "uatom")
+        );
+
+        // 11.5 winning bid split 90/10
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?,
+            coin(11_000_000u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(treasury.clone(), "uatom")?,
+            coin(1_350_000u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(referrer.clone(), "uatom")?,
+            coin(150_000u128, "uatom")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn creator_royalties() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+        let artist = Addr::unchecked("artist");
+
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alex, coins(100_000_000u128, "uatom"))
+                .unwrap();
+
+            router
+                .bank
+                .init_balance(storage, &ann, coins(100_000_000u128, "uatom"))
+                .unwrap();
+        });
+
+        let nft_id = app.store_code(royalty_nft_contract());
+        let nft = app
+            .instantiate_contract(nft_id, owner.clone(), &Empty {}, &[], "nft", None)
+            .unwrap();
+
+        let contract_id = app.store_code(bid_contract());
+
+        let err = app
+            .instantiate_contract(
+                contract_id,
+                owner.clone(),
+                &BidInstantiateMsg {
+                    pay_royalties: Some(true),
+                    ..Default::default()
+                },
+                &[],
+                "label",
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<BidError>().unwrap(),
+            BidError::RoyaltiesWithoutItem
+        );
+
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                item: Some(AuctionItem {
+                    contract: nft.to_string(),
+                    token_id: "1".to_string(),
+                }),
+                pay_royalties: Some(true),
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_500_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(bid.bid(&mut app, &ann, &coins(12_500_000, "uatom")), Ok(()));
+
+        let res = bid
+            .execute_with_response(&mut app, &owner, &BidExecuteMsg::Close {}, &[])
+            .unwrap();
+        let wasm = res.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+        let attr = |key: &str| {
+            wasm.attributes
+                .iter()
+                .find(|attr| attr.key == key)
+                .map(|attr| attr.value.clone())
+        };
+        assert_eq!(attr("royalty_amount"), Some("1200000".to_string()));
+        assert_eq!(attr("royalty_recipient"), Some(artist.to_string()));
+        assert_eq!(attr("seller_amount"), Some("10800000".to_string()));
+
+        // 10.8 of sale and 1 of commissions
+        assert_eq!(
+            app.wrap().query_balance(artist.clone(), "uatom")?,
+            coin(1_200_000u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?,
+            coin(11_800_000u128, "uatom")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn ownership_transfer() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let multisig = Addr::unchecked("multisig");
+        let alex = Addr::unchecked("alex");
+
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alex, coins(100_000_000u128, "uatom"))
+                .unwrap();
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            None,
+            None,
+        )?;
+
+        let propose = |expiry| BidExecuteMsg::ProposeNewOwner {
+            new_owner: multisig.to_string(),
+            expiry,
+        };
+        let expiry = Expiration::AtHeight(app.block_info().height + 10);
+
+        assert_eq!(
+            bid.execute(&mut app, &alex, &propose(None), &[]),
+            Err(BidError::OnlyOwner)
+        );
+        assert_eq!(
+            bid.execute(&mut app, &owner, &propose(Some(expiry)), &[]),
+            Ok(())
+        );
+        assert_eq!(
+            bid.pending_owner(&app)?,
+            Some(PendingOwnership {
+                new_owner: multisig.clone(),
+                expiry: Some(expiry),
+            })
+        );
+        assert_eq!(
+            bid.execute(&mut app, &alex, &BidExecuteMsg::AcceptOwnership {}, &[]),
+            Err(BidError::OnlyPendingOwner)
+        );
+
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &owner,
+                &BidExecuteMsg::CancelOwnershipProposal {},
+                &[]
+            ),
+            Ok(())
+        );
+        assert_eq!(bid.pending_owner(&app)?, None);
+        assert_eq!(
+            bid.execute(&mut app, &multisig, &BidExecuteMsg::AcceptOwnership {}, &[]),
+            Err(BidError::NoPendingOwner)
+        );
+
+        // expired proposal cannot be accepted
+        assert_eq!(
+            bid.execute(&mut app, &owner, &propose(Some(expiry)), &[]),
+            Ok(())
+        );
+        app.update_block(|block| block.height += 10);
+        assert_eq!(
+            bid.execute(&mut app, &multisig, &BidExecuteMsg::AcceptOwnership {}, &[]),
+            Err(BidError::OwnershipProposalExpired)
+        );
+
+        assert_eq!(bid.execute(&mut app, &owner, &propose(None), &[]), Ok(()));
+        assert_eq!(
+            bid.execute(&mut app, &multisig, &BidExecuteMsg::AcceptOwnership {}, &[]),
+            Ok(())
+        );
+        assert_eq!(bid.owner(&app)?, multisig.to_string());
+        assert_eq!(bid.pending_owner(&app)?, None);
+        assert_eq!(bid.roles(&app)?.admins, vec![multisig.clone()]);
+
+        // a pending owner that bids in the meantime cannot accept
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &multisig,
+                &BidExecuteMsg::ProposeNewOwner {
+                    new_owner: alex.to_string(),
+                    expiry: None,
+                },
+                &[]
+            ),
+            Ok(())
+        );
+        // commission follows the new owner
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(
+            bid.execute(&mut app, &alex, &BidExecuteMsg::AcceptOwnership {}, &[]),
+            Err(BidError::OwnerCannotBid)
+        );
+        assert_eq!(bid.owner(&app)?, multisig.to_string());
+        assert_eq!(
+            app.wrap().query_balance(multisig.clone(), "uatom")?,
+            coin(500_000u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?,
+            coin(0u128, "uatom")
+        );
+
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &multisig,
+                &BidExecuteMsg::RenounceOwnership {},
+                &[]
+            ),
+            Err(BidError::BidNotClosed)
+        );
+        assert_eq!(bid.close(&mut app, &multisig, &[]), Ok(()));
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &multisig,
+                &BidExecuteMsg::RenounceOwnership {},
+                &[]
+            ),
+            Ok(())
+        );
+        let no_owner: Option<Addr> = app
+            .wrap()
+            .query_wasm_smart(bid.0.clone(), &BidQueryMsg::Owner {})?;
+        assert_eq!(no_owner, None);
+        assert!(bid.owner(&app).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn roles_and_expiration() -> StdResult<()> {
+        let seller = Addr::unchecked("seller");
+        let admin = Addr::unchecked("admin");
+        let keeper = Addr::unchecked("keeper");
+        let alex = Addr::unchecked("alex");
+
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alex, coins(100_000_000u128, "uatom"))
+                .unwrap();
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let expiration = Expiration::AtHeight(app.block_info().height + 100);
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &seller,
+            "label".to_string(),
+            BidInstantiateMsg {
+                admins: Some(vec![admin.to_string()]),
+                operators: Some(vec![keeper.to_string()]),
+                expiration: Some(expiration),
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(
+            bid.roles(&app)?,
+            RolesResponse {
+                admins: vec![admin.clone()],
+                operators: vec![keeper.clone()],
+            }
+        );
+
+        // the seller has no operational control
+        let add_bot = BidExecuteMsg::AddRole {
+            address: "bot".to_string(),
+            role: Role::Operator,
+        };
+        assert_eq!(
+            bid.execute(&mut app, &seller, &add_bot, &[]),
+            Err(BidError::OnlyAdmin)
+        );
+        assert_eq!(bid.execute(&mut app, &admin, &add_bot, &[]), Ok(()));
+        assert_eq!(
+            bid.roles(&app)?.operators,
+            vec![Addr::unchecked("bot"), keeper.clone()]
+        );
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &admin,
+                &BidExecuteMsg::RemoveRole {
+                    address: admin.to_string(),
+                    role: Role::Admin,
+                },
+                &[]
+            ),
+            Err(BidError::LastAdmin)
+        );
+
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+
+        assert_eq!(
+            bid.close(&mut app, &keeper, &[]),
+            Err(BidError::AuctionNotExpired)
+        );
+        assert_eq!(
+            bid.close(&mut app, &alex, &[]),
+            Err(BidError::OnlyOwnerCanClose)
+        );
+
+        app.update_block(|block| block.height += 100);
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Err(BidError::AuctionExpired)
+        );
+        assert_eq!(bid.close(&mut app, &keeper, &[]), Ok(()));
+        assert_eq!(bid.winner(&app)?, Some(alex.to_string()));
+
+        // proceeds still go to the seller
+        assert_eq!(
+            app.wrap().query_balance(seller.clone(), "uatom")?,
+            coin(10_000_000u128, "uatom")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cancel() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alex, coins(100_000_000u128, "uatom"))
+                .unwrap();
+
+            router
+                .bank
+                .init_balance(storage, &ann, coins(100_000_000u128, "uatom"))
+                .unwrap();
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                commission_policy: Some(CommissionPolicy::Refundable),
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(bid.bid(&mut app, &ann, &coins(12_000_000, "uatom")), Ok(()));
+
+        assert_eq!(
+            bid.execute(&mut app, &alex, &BidExecuteMsg::Cancel {}, &[]),
+            Err(BidError::OnlyAdmin)
+        );
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::Cancel {}, &[]),
+            Ok(())
+        );
+        assert!(bid.closed(&app)?);
+        assert_eq!(bid.winner(&app)?, None);
+        assert_eq!(
+            bid.close(&mut app, &owner, &[]),
+            Err(BidError::BidAlreadyClosed)
+        );
+
+        // everybody gets bid and commission back
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(bid.retract(&mut app, &ann, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(alex.clone(), "uatom")?,
+            coin(100_000_000u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(ann.clone(), "uatom")?,
+            coin(100_000_000u128, "uatom")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn access_lists() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+        let bob = Addr::unchecked("bob");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann, &bob] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                allowlist_only: Some(true),
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Err(BidError::NotWhitelisted)
+        );
+
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &alex,
+                &BidExecuteMsg::UpdateAllowlist {
+                    add: vec![alex.to_string()],
+                    remove: vec![],
+                },
+                &[]
+            ),
+            Err(BidError::OnlyAdmin)
+        );
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &owner,
+                &BidExecuteMsg::UpdateAllowlist {
+                    add: vec![alex.to_string(), ann.to_string(), bob.to_string()],
+                    remove: vec![bob.to_string()],
+                },
+                &[]
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &owner,
+                &BidExecuteMsg::UpdateDenylist {
+                    add: vec![ann.to_string()],
+                    remove: vec![],
+                },
+                &[]
+            ),
+            Ok(())
+        );
+
+        let allowlist: Vec<Addr> = app.wrap().query_wasm_smart(
+            bid.0.clone(),
+            &BidQueryMsg::Allowlist {
+                start_after: None,
+                limit: Some(1),
+            },
+        )?;
+        assert_eq!(allowlist, vec![alex.clone()]);
+        let allowlist: Vec<Addr> = app.wrap().query_wasm_smart(
+            bid.0.clone(),
+            &BidQueryMsg::Allowlist {
+                start_after: Some(alex.to_string()),
+                limit: None,
+            },
+        )?;
+        assert_eq!(allowlist, vec![ann.clone()]);
+        let denylist: Vec<Addr> = app.wrap().query_wasm_smart(
+            bid.0.clone(),
+            &BidQueryMsg::Denylist {
+                start_after: None,
+                limit: None,
+            },
+        )?;
+        assert_eq!(denylist, vec![ann.clone()]);
+
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(
+            bid.bid(&mut app, &ann, &coins(12_000_000, "uatom")),
+            Err(BidError::Banned)
+        );
+        assert_eq!(
+            bid.bid(&mut app, &bob, &coins(12_000_000, "uatom")),
+            Err(BidError::NotWhitelisted)
+        );
+
+        // the denylist still applies once the sale is public
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &owner,
+                &BidExecuteMsg::SetAllowlistOnly { enabled: false },
+                &[]
+            ),
+            Ok(())
+        );
+        assert_eq!(bid.bid(&mut app, &bob, &coins(12_000_000, "uatom")), Ok(()));
+        assert_eq!(
+            bid.bid(&mut app, &ann, &coins(14_000_000, "uatom")),
+            Err(BidError::Banned)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn merkle_allowlist() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let bidders = ["alex", "ann", "bob", "carl", "dave"].map(Addr::unchecked);
+        let eve = Addr::unchecked("eve");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in bidders.iter().chain([&eve]) {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let tree = MerkleTree::new(&bidders);
+        let bid_with_proof = |proof: Option<Vec<String>>| BidExecuteMsg::Bid {
+            referrer: None,
+            proof,
+            ibc_origin: None,
+            expires_at: None,
+            on_behalf_of: None,
+        };
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            None,
+            None,
+        )?;
+
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &owner,
+                &BidExecuteMsg::SetMerkleRoot {
+                    root: Some("not hex".to_string())
+                },
+                &[]
+            ),
+            Err(BidError::InvalidMerkleRoot)
+        );
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &owner,
+                &BidExecuteMsg::SetMerkleRoot { root: tree.root() },
+                &[]
+            ),
+            Ok(())
+        );
+        let root: Option<String> = app
+            .wrap()
+            .query_wasm_smart(bid.0.clone(), &BidQueryMsg::MerkleRoot {})?;
+        assert_eq!(root, tree.root());
+
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &bidders[0],
+                &bid_with_proof(tree.proof("alex")),
+                &coins(10_000_000, "uatom")
+            ),
+            Ok(())
+        );
+        // the odd leaf of the tree, a dry run checks the proof as well
+        let simulated: SimulateBidResponse = app.wrap().query_wasm_smart(
+            bid.0.clone(),
+            &BidQueryMsg::SimulateBid {
+                bidder: bidders[4].to_string(),
+                funder: None,
+                funds: coins(12_000_000, "uatom"),
+                referrer: None,
+                proof: tree.proof("dave"),
+                expires_at: None,
+            },
+        )?;
+        assert_eq!(simulated.error, None);
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &bidders[4],
+                &bid_with_proof(tree.proof("dave")),
+                &coins(12_000_000, "uatom")
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &bidders[2],
+                &bid_with_proof(tree.proof("ann")),
+                &coins(14_000_000, "uatom")
+            ),
+            Err(BidError::InvalidMerkleProof)
+        );
+
+        assert_eq!(tree.proof("eve"), None);
+        assert_eq!(
+            bid.bid(&mut app, &eve, &coins(14_000_000, "uatom")),
+            Err(BidError::NotWhitelisted)
+        );
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &eve,
+                &bid_with_proof(tree.proof("alex")),
+                &coins(14_000_000, "uatom")
+            ),
+            Err(BidError::InvalidMerkleProof)
+        );
+
+        // stored allowlist entries need no proof
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &owner,
+                &BidExecuteMsg::UpdateAllowlist {
+                    add: vec![eve.to_string()],
+                    remove: vec![],
+                },
+                &[]
+            ),
+            Ok(())
+        );
+        assert_eq!(bid.bid(&mut app, &eve, &coins(14_000_000, "uatom")), Ok(()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn compliance_gate() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let gate_id = app.store_code(gate_contract());
+        let gate = app
+            .instantiate_contract(gate_id, owner.clone(), &Empty {}, &[], "gate", None)
+            .unwrap();
+        let set_allowed = |app: &mut App, address: &Addr, allowed: bool| {
+            app.execute_contract(
+                owner.clone(),
+                gate.clone(),
+                &MockGateExecuteMsg::SetAllowed {
+                    address: address.to_string(),
+                    allowed,
+                },
+                &[],
+            )
+            .unwrap();
+        };
+        set_allowed(&mut app, &alex, true);
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                gate: Some(gate.to_string()),
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(
+            bid.bid(&mut app, &ann, &coins(10_000_000, "uatom")),
+            Err(BidError::RejectedByGate)
+        );
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+
+        set_allowed(&mut app, &ann, true);
+        assert_eq!(bid.bid(&mut app, &ann, &coins(12_000_000, "uatom")), Ok(()));
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+
+        // payouts are gated as well
+        set_allowed(&mut app, &alex, false);
+        assert_eq!(
+            bid.retract(&mut app, &alex, &[], None),
+            Err(BidError::RejectedByGate)
+        );
+        set_allowed(&mut app, &alex, true);
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn pause() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let msg = BidInstantiateMsg {
+            max_pause_duration: Some(3_600),
+            ..Default::default()
+        };
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            msg.clone(),
+        )?;
+
+        assert_eq!(bid.status(&app)?, AuctionStatus::Open);
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(bid.bid(&mut app, &ann, &coins(12_000_000, "uatom")), Ok(()));
+
+        assert_eq!(
+            bid.execute(&mut app, &alex, &BidExecuteMsg::Pause {}, &[]),
+            Err(BidError::OnlyAdmin)
+        );
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::Pause {}, &[]),
+            Ok(())
+        );
+        assert_eq!(
+            bid.status(&app)?,
+            AuctionStatus::Paused {
+                cancels_at: Some(app.block_info().time.plus_seconds(3_600))
+            }
+        );
+
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Err(BidError::Paused)
+        );
+        assert_eq!(bid.close(&mut app, &owner, &[]), Err(BidError::Paused));
+
+        // only the leading bid stays locked
+        assert_eq!(bid.retract(&mut app, &ann, &[], None), Err(BidWinner));
+        // nobody else can retract it for the bidder while the auction runs
+        assert_eq!(
+            bid.retract(&mut app, &ann, &[], Some(alex.to_string())),
+            Err(BidError::OnlyBidderCanRetract)
+        );
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(alex.clone(), "uatom")?,
+            coin(99_500_000u128, "uatom")
+        );
+
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::Unpause {}, &[]),
+            Ok(())
+        );
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::Unpause {}, &[]),
+            Err(BidError::NotPaused)
+        );
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Err(BidError::RetractAlreadyDone)
+        );
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+
+        // a pause running too long cancels the auction
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            msg,
+        )?;
+        assert_eq!(bid.bid(&mut app, &ann, &coins(10_000_000, "uatom")), Ok(()));
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::Pause {}, &[]),
+            Ok(())
+        );
+        app.update_block(|block| block.time = block.time.plus_seconds(3_600));
+        assert_eq!(bid.status(&app)?, AuctionStatus::Cancelled);
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::Unpause {}, &[]),
+            Err(BidError::AuctionCancelled)
+        );
+        assert_eq!(bid.retract(&mut app, &ann, &[], None), Ok(()));
+        assert!(bid.closed(&app)?);
+        assert_eq!(
+            app.wrap().query_balance(ann.clone(), "uatom")?,
+            coin(87_500_000u128, "uatom")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_live_auction() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let legacy_id = app.store_code(legacy_bid_contract());
+        let contract_id = app.store_code(bid_contract());
+        let legacy = |app: &mut App, contract: &str, version: &str| {
+            app.instantiate_contract(
+                legacy_id,
+                owner.clone(),
+                &LegacyInstantiateMsg {
+                    contract: contract.to_string(),
+                    version: version.to_string(),
+                },
+                &[],
+                "legacy",
+                Some(owner.to_string()),
+            )
+            .map(BidContract)
+            .unwrap()
+        };
+        let migrate = |app: &mut App, bid: &BidContract| {
+            app.migrate_contract(owner.clone(), bid.0.clone(), &BidMigrateMsg {}, contract_id)
+                .map(|_| ())
+                .map_err(|err| err.downcast::<BidError>().unwrap())
+        };
+
+        // 0.0.1 cannot be reconciled, its state is only complete once migrated
+        let legacy_bid = |app: &mut App, bid: &BidContract, sender: &Addr, amount: u128| {
+            let msg = BidExecuteMsg::Bid {
+                referrer: None,
+                proof: None,
+                ibc_origin: None,
+                expires_at: None,
+                on_behalf_of: None,
+            };
+            app.execute_contract(sender.clone(), bid.0.clone(), &msg, &coins(amount, "uatom"))
+                .map(|_| ())
+        };
+        let bid = legacy(&mut app, "crates.io:cw-bid", "0.0.1");
+        legacy_bid(&mut app, &bid, &alex, 10_000_000).unwrap();
+        legacy_bid(&mut app, &bid, &ann, 12_000_000).unwrap();
+
+        assert_eq!(migrate(&mut app, &bid), Ok(()));
+        let version = query_contract_info(&app.wrap(), bid.0.to_string())?;
+        assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+        // state written by 0.0.1 is kept and new settings get their defaults
+        assert_eq!(bid.highest_bidder(&app)?, Some(ann.clone()));
+        assert_eq!(bid.highest_bid(&app)?, Uint128::new(12_000_000u128));
+        assert_eq!(bid.commission_policy(&app)?, CommissionPolicy::Kept);
+        assert_eq!(bid.roles(&app)?.admins, vec![owner.clone()]);
+        assert_eq!(bid.status(&app)?, AuctionStatus::Open);
+
+        assert_eq!(bid.bid(&mut app, &alex, &coins(5_000_000, "uatom")), Ok(()));
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+        assert_eq!(bid.retract(&mut app, &ann, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?,
+            coin(15_500_000u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(ann.clone(), "uatom")?,
+            coin(99_500_000u128, "uatom")
+        );
+
+        // migrating again to the same version is fine
+        assert_eq!(migrate(&mut app, &bid), Ok(()));
+
+        let foreign = legacy(&mut app, "crates.io:cw20-base", "0.0.1");
+        assert_eq!(
+            migrate(&mut app, &foreign),
+            Err(BidError::ForeignContract {
+                contract: "crates.io:cw20-base".to_string()
+            })
+        );
+
+        let newer = legacy(&mut app, "crates.io:cw-bid", "9.0.0");
+        assert_eq!(
+            migrate(&mut app, &newer),
+            Err(BidError::CannotDowngrade {
+                from: "9.0.0".to_string(),
+                to: env!("CARGO_PKG_VERSION").to_string(),
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn governance_sudo() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let msg = BidInstantiateMsg {
+            commission: Some(Uint128::new(1_000_000u128)),
+            max_pause_duration: Some(3_600),
+            ..Default::default()
+        };
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            msg.clone(),
+        )?;
+
+        let commission: Uint128 = app
+            .wrap()
+            .query_wasm_smart(bid.0.clone(), &BidQueryMsg::GetCommission {})?;
+        assert_eq!(commission, Uint128::new(1_000_000u128));
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+
+        assert_eq!(
+            bid.sudo(
+                &mut app,
+                &BidSudoMsg::UpdateCommission {
+                    commission: None,
+                    commission_split: Some(vec![FeeShare {
+                        recipient: FeeRecipient::Owner {},
+                        weight: Decimal::percent(50),
+                    }]),
+                }
+            ),
+            Err(BidError::InvalidFeeSplit)
+        );
+        assert_eq!(
+            bid.sudo(
+                &mut app,
+                &BidSudoMsg::UpdateCommission {
+                    commission: Some(Uint128::new(250_000u128)),
+                    commission_split: None,
+                }
+            ),
+            Ok(())
+        );
+        assert_eq!(bid.bid(&mut app, &ann, &coins(12_000_000, "uatom")), Ok(()));
+        assert_eq!(bid.highest_bid(&app)?, Uint128::new(12_000_000u128));
+
+        // a frozen auction rejects every execute message
+        assert_eq!(bid.sudo(&mut app, &BidSudoMsg::Freeze {}), Ok(()));
+        assert_eq!(
+            bid.sudo(&mut app, &BidSudoMsg::Freeze {}),
+            Err(BidError::Frozen)
+        );
+        assert_eq!(bid.status(&app)?, AuctionStatus::Frozen);
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Err(BidError::Frozen)
+        );
+        assert_eq!(bid.close(&mut app, &owner, &[]), Err(BidError::Frozen));
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::Unpause {}, &[]),
+            Err(BidError::Frozen)
+        );
+
+        assert_eq!(bid.sudo(&mut app, &BidSudoMsg::ForceClose {}), Ok(()));
+        assert_eq!(
+            bid.sudo(&mut app, &BidSudoMsg::ForceClose {}),
+            Err(BidError::BidAlreadyClosed)
+        );
+        assert_eq!(
+            bid.retract(&mut app, &alex, &[], None),
+            Err(BidError::Frozen)
+        );
+        assert_eq!(bid.sudo(&mut app, &BidSudoMsg::Unfreeze {}), Ok(()));
+        assert_eq!(
+            bid.sudo(&mut app, &BidSudoMsg::Unfreeze {}),
+            Err(BidError::NotFrozen)
+        );
+
+        assert_eq!(bid.status(&app)?, AuctionStatus::Closed);
+        assert_eq!(bid.winner(&app)?, Some(ann.to_string()));
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(alex.clone(), "uatom")?,
+            coin(99_000_000u128, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?,
+            coin(13_000_000u128, "uatom")
+        );
+
+        // force cancel refunds every bidder
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            msg.clone(),
+        )?;
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(bid.sudo(&mut app, &BidSudoMsg::ForceCancel {}), Ok(()));
+        assert_eq!(bid.status(&app)?, AuctionStatus::Cancelled);
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(alex.clone(), "uatom")?,
+            coin(98_000_000u128, "uatom")
+        );
+
+        // an auction cancelled by a too long pause cannot be forced closed
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            msg,
+        )?;
+        assert_eq!(bid.bid(&mut app, &ann, &coins(10_000_000, "uatom")), Ok(()));
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::Pause {}, &[]),
+            Ok(())
+        );
+        assert_eq!(bid.sudo(&mut app, &BidSudoMsg::Freeze {}), Ok(()));
+        app.update_block(|block| block.time = block.time.plus_seconds(3_600));
+        assert_eq!(
+            bid.sudo(&mut app, &BidSudoMsg::ForceClose {}),
+            Err(BidError::AuctionCancelled)
+        );
+
+        Ok(())
+    }
+
+    // ibc-hooks refunds are ibc transfers, which cw-multi-test cannot execute,
+    // so the hook calls are simulated on the entry points directly
+    #[test]
+    fn ibc_hooks_bid() -> Result<(), BidError> {
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: HookApi::default(),
+            querier: MockQuerier::default(),
+            custom_query_type: PhantomData,
+        };
+        let env = mock_env();
+
+        let origin = IbcOrigin {
+            channel: "channel-0".to_string(),
+            sender: "osmo1alice".to_string(),
+        };
+        let intermediate = intermediate_sender(&deps.api, &origin.channel, &origin.sender)?;
+        let hook_bid = |origin: IbcOrigin| BidExecuteMsg::Bid {
+            referrer: None,
+            proof: None,
+            ibc_origin: Some(origin),
+            expires_at: None,
+            on_behalf_of: None,
+        };
+
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            BidInstantiateMsg::default(),
+        )?;
+
+        // the origin must be the one the intermediate account is derived from
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(intermediate.as_str(), &coins(10_000_000, "uatom")),
+                hook_bid(IbcOrigin {
+                    sender: "osmo1mallory".to_string(),
+                    ..origin.clone()
+                }),
+            ),
+            Err(BidError::InvalidIbcOrigin)
+        );
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("ann", &coins(10_000_000, "uatom")),
+                hook_bid(origin.clone()),
+            ),
+            Err(BidError::InvalidIbcOrigin)
+        );
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(intermediate.as_str(), &coins(10_000_000, "uatom")),
+            hook_bid(origin.clone()),
+        )?;
+        let stored: Option<IbcOrigin> = from_binary(&query(
+            deps.as_ref(),
+            env.clone(),
+            BidQueryMsg::IbcOriginFor {
+                address: intermediate.to_string(),
+            },
+        )?)?;
+        assert_eq!(stored, Some(origin.clone()));
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("ann", &coins(12_000_000, "uatom")),
+            BidExecuteMsg::Bid {
+                referrer: None,
+                proof: None,
+                ibc_origin: None,
+                expires_at: None,
+                on_behalf_of: None,
+            },
+        )?;
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            BidExecuteMsg::Close {},
+        )?;
+
+        // anyone can trigger the refund, it goes back to the origin chain
+        let retract = |deps: DepsMut| {
+            execute(
+                deps,
+                env.clone(),
+                mock_info("ann", &[]),
+                BidExecuteMsg::Retract {
+                    receiver: Some(intermediate.to_string()),
+                },
+            )
+        };
+        let res = retract(deps.as_mut())?;
+        let res_id = res.messages[0].id;
+        // the reply records the packet sequence of the MsgTransferResponse
+        let transfer_sent = |deps: DepsMut, sequence: u8| {
+            reply(
+                deps,
+                env.clone(),
+                Reply {
+                    id: res_id,
+                    result: SubMsgResult::Ok(SubMsgResponse {
+                        events: vec![],
+                        data: Some(Binary(vec![0x08, sequence])),
+                    }),
+                },
+            )
+        };
+        assert_eq!(res.messages[0].reply_on, ReplyOn::Success);
+        assert_eq!(
+            res.messages[0].msg,
+            transfer_with_callback(
+                "channel-0",
+                &env.contract.address,
+                "osmo1alice",
+                &coin(9_500_000u128, "uatom"),
+                env.block.time.plus_seconds(86_400),
+            )
+        );
+        match &res.messages[0].msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/ibc.applications.transfer.v1.MsgTransfer");
+                let memo = format!(r#"{{"ibc_callback":"{}"}}"#, env.contract.address);
+                assert!(value.windows(memo.len()).any(|w| w == memo.as_bytes()));
+            }
+            msg => panic!("unexpected refund {:?}", msg),
+        }
+        transfer_sent(deps.as_mut(), 7)?;
+        assert_eq!(retract(deps.as_mut()), Err(BidError::RetractAlreadyDone));
+
+        // a timed out refund is back in escrow and can be retracted again
+        let lifecycle = |deps: DepsMut, lifecycle| {
+            sudo(
+                deps,
+                env.clone(),
+                BidSudoMsg::IbcLifecycleComplete(lifecycle),
+            )
+        };
+        lifecycle(
+            deps.as_mut(),
+            IbcLifecycleComplete::IbcTimeout {
+                channel: "channel-0".to_string(),
+                sequence: 7,
+            },
+        )?;
+        retract(deps.as_mut())?;
+        transfer_sent(deps.as_mut(), 8)?;
+
+        let ack = IbcLifecycleComplete::IbcAck {
+            channel: "channel-0".to_string(),
+            sequence: 8,
+            ack: "AQ==".to_string(),
+            success: true,
+        };
+        lifecycle(deps.as_mut(), ack.clone())?;
+        assert_eq!(retract(deps.as_mut()), Err(BidError::RetractAlreadyDone));
+        assert!(lifecycle(deps.as_mut(), ack).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn ibc_controller() -> Result<(), BidError> {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            BidInstantiateMsg::default(),
+        )?;
+
+        assert!(ibc_channel_open(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_open_try("channel-0", IbcOrder::Unordered, "ics20-1"),
+        )
+        .is_err());
+        assert!(ibc_channel_open(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_open_try("channel-0", IbcOrder::Ordered, IBC_VERSION),
+        )
+        .is_err());
+        ibc_channel_open(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_open_try("channel-0", IbcOrder::Unordered, IBC_VERSION),
+        )?;
+        ibc_channel_connect(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_connect_ack("channel-0", IbcOrder::Unordered, IBC_VERSION),
+        )?;
+        assert!(ibc_channel_open(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_open_try("channel-1", IbcOrder::Unordered, IBC_VERSION),
+        )
+        .is_err());
+
+        let receive = |deps: DepsMut, packet: ControllerPacket| -> Result<PacketAck, BidError> {
+            let res = ibc_packet_receive(
+                deps,
+                env.clone(),
+                mock_ibc_packet_recv("channel-0", &packet)?,
+            )?;
+            Ok(from_binary(&res.acknowledgement)?)
+        };
+        let amount_ack =
+            |amount: u128| PacketAck::Result(to_binary(&Uint128::new(amount)).unwrap());
+
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::Bid {
+                    bidder: "osmo1bob".to_string(),
+                    amount: Uint128::new(5_000_000u128),
+                    proof: None,
+                }
+            )?,
+            amount_ack(5_000_000)
+        );
+        // remote bidders go through the same access checks
+        let allowlist_only = |deps: DepsMut, enabled| {
+            execute(
+                deps,
+                env.clone(),
+                mock_info("owner", &[]),
+                BidExecuteMsg::SetAllowlistOnly { enabled },
+            )
+        };
+        allowlist_only(deps.as_mut(), true)?;
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::Bid {
+                    bidder: "osmo1alice".to_string(),
+                    amount: Uint128::new(10_000_000u128),
+                    proof: None,
+                }
+            )?,
+            PacketAck::Error(BidError::NotWhitelisted.to_string())
+        );
+        allowlist_only(deps.as_mut(), false)?;
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::Bid {
+                    bidder: "osmo1alice".to_string(),
+                    amount: Uint128::new(10_000_000u128),
+                    proof: None,
+                }
+            )?,
+            amount_ack(10_000_000)
+        );
+        // a remote leader pays no commission here
+        let highest: Uint128 = from_binary(&query(
+            deps.as_ref(),
+            env.clone(),
+            BidQueryMsg::HighestBid {},
+        )?)?;
+        assert_eq!(highest, Uint128::new(10_000_000u128));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("ann", &coins(12_000_000, "uatom")),
+            BidExecuteMsg::Bid {
+                referrer: None,
+                proof: None,
+                ibc_origin: None,
+                expires_at: None,
+                on_behalf_of: None,
+            },
+        )?;
+
+        // refused bids are acked as errors so the controller releases the escrow
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::Bid {
+                    bidder: "osmo1alice".to_string(),
+                    amount: Uint128::new(1_000_000u128),
+                    proof: None,
+                }
+            )?,
+            PacketAck::Error(BidError::BidTooLow.to_string())
+        );
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::Retract {
+                    bidder: "osmo1bob".to_string(),
+                }
+            )?,
+            PacketAck::Error(BidError::BidNotClosed.to_string())
+        );
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::Bid {
+                    bidder: "osmo1alice".to_string(),
+                    amount: Uint128::new(3_000_000u128),
+                    proof: None,
+                }
+            )?,
+            amount_ack(13_000_000)
+        );
+
+        // a remote winner is settled by a packet to the controller
+        let settle = AuctionPacket::Settle {
+            bidder: "osmo1alice".to_string(),
+            amount: Uint128::new(13_000_000u128),
+            receiver: env.contract.address.to_string(),
+        };
+        let close = |deps: DepsMut| {
+            execute(
+                deps,
+                mock_env(),
+                mock_info("owner", &[]),
+                BidExecuteMsg::Close {},
+            )
+        };
+        let res = close(deps.as_mut())?;
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Ibc(IbcMsg::SendPacket {
+                channel_id: "channel-0".to_string(),
+                data: to_binary(&settle)?,
+                timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(86_400)),
+            })
+        );
+        let winner: Option<Addr> =
+            from_binary(&query(deps.as_ref(), env.clone(), BidQueryMsg::BidWinner)?)?;
+        assert_eq!(winner, Some(Addr::unchecked("channel-0/osmo1alice")));
+
+        // a timed out settlement rolls the close back
+        let res = ibc_packet_timeout(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_packet_timeout("channel-0", &settle)?,
+        )?;
+        assert_eq!(
+            res.events.iter().find_map(AuctionReopened::from_event),
+            Some(AuctionReopened {
+                auction_id: env.contract.address.to_string(),
+                previous_winner: "channel-0/osmo1alice".to_string(),
+            })
+        );
+        let status: AuctionStatus =
+            from_binary(&query(deps.as_ref(), env.clone(), BidQueryMsg::Status {})?)?;
+        assert_eq!(status, AuctionStatus::Open);
+
+        close(deps.as_mut())?;
+        ibc_packet_ack(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_packet_ack(
+                "channel-0",
+                &settle,
+                IbcAcknowledgement::new(to_binary(&PacketAck::Result(Binary::default()))?),
+            )?,
+        )?;
+        let status: AuctionStatus =
+            from_binary(&query(deps.as_ref(), env.clone(), BidQueryMsg::Status {})?)?;
+        assert_eq!(status, AuctionStatus::Closed);
+
+        // the winning escrow is paid out once transferred here, next to ann's escrow
+        let collect = |deps: DepsMut| {
+            execute(
+                deps,
+                env.clone(),
+                mock_info("anyone", &[]),
+                BidExecuteMsg::CollectProceeds {},
+            )
+        };
+        deps.querier
+            .update_balance(env.contract.address.clone(), coins(11_500_000, "uatom"));
+        assert_eq!(collect(deps.as_mut()), Err(BidError::ProceedsNotArrived));
+        deps.querier
+            .update_balance(env.contract.address.clone(), coins(24_500_000, "uatom"));
+        let res = collect(deps.as_mut())?;
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".to_string(),
+                amount: coins(13_000_000u128, "uatom"),
+            })
+        );
+        assert_eq!(collect(deps.as_mut()), Err(BidError::NoProceedsPending));
+
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::Retract {
+                    bidder: "osmo1alice".to_string(),
+                }
+            )?,
+            PacketAck::Error(BidError::BidWinner.to_string())
+        );
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::Retract {
+                    bidder: "osmo1bob".to_string(),
+                }
+            )?,
+            amount_ack(5_000_000)
+        );
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::Retract {
+                    bidder: "osmo1bob".to_string(),
+                }
+            )?,
+            PacketAck::Error(BidError::RetractAlreadyDone.to_string())
+        );
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("ann", &[]),
+                BidExecuteMsg::Retract {
+                    receiver: Some("channel-0/osmo1bob".to_string()),
+                },
+            ),
+            Err(BidError::RemoteBid)
+        );
+
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("ann", &[]),
+            BidExecuteMsg::Retract { receiver: None },
+        )?;
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "ann".to_string(),
+                amount: coins(11_500_000u128, "uatom"),
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn controller_opens_auction() -> Result<(), BidError> {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            BidInstantiateMsg {
+                controller_opens: Some(true),
+                ..Default::default()
+            },
+        )?;
+        ibc_channel_open(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_open_try("channel-0", IbcOrder::Unordered, IBC_VERSION),
+        )?;
+        ibc_channel_connect(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_connect_ack("channel-0", IbcOrder::Unordered, IBC_VERSION),
+        )?;
+
+        let receive = |deps: DepsMut, packet: ControllerPacket| -> Result<PacketAck, BidError> {
+            let res = ibc_packet_receive(
+                deps,
+                env.clone(),
+                mock_ibc_packet_recv("channel-0", &packet)?,
+            )?;
+            Ok(from_binary(&res.acknowledgement)?)
+        };
+        let bid = |deps: DepsMut| {
+            execute(
+                deps,
+                env.clone(),
+                mock_info("ann", &coins(2_000_000, "uatom")),
+                BidExecuteMsg::Bid {
+                    referrer: None,
+                    proof: None,
+                    ibc_origin: None,
+                    expires_at: None,
+                    on_behalf_of: None,
+                },
+            )
+        };
+        let status = |deps: Deps| -> StdResult<AuctionStatus> {
+            from_binary(&query(deps, env.clone(), BidQueryMsg::Status {})?)
+        };
+
+        // nobody bids before the controller opens the auction
+        assert_eq!(
+            status(deps.as_ref())?,
+            AuctionStatus::Paused { cancels_at: None }
+        );
+        assert_eq!(bid(deps.as_mut()), Err(BidError::Paused));
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::OpenAuction {
+                    expiration: Some(Expiration::AtHeight(env.block.height)),
+                }
+            )?,
+            PacketAck::Error(BidError::AuctionExpired.to_string())
+        );
+
+        let expiration = Expiration::AtHeight(env.block.height + 100);
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::OpenAuction {
+                    expiration: Some(expiration),
+                }
+            )?,
+            PacketAck::Result(Binary::default())
+        );
+        assert_eq!(status(deps.as_ref())?, AuctionStatus::Open);
+        let stored: Option<Expiration> = from_binary(&query(
+            deps.as_ref(),
+            env.clone(),
+            BidQueryMsg::GetExpiration {},
+        )?)?;
+        assert_eq!(stored, Some(expiration));
+        bid(deps.as_mut())?;
+
+        // the controller cannot lift a pause of the owner
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            BidExecuteMsg::Pause {},
+        )?;
+        assert_eq!(
+            receive(
+                deps.as_mut(),
+                ControllerPacket::OpenAuction { expiration: None }
+            )?,
+            PacketAck::Error(BidError::NotAwaitingController.to_string())
+        );
+        assert_eq!(
+            status(deps.as_ref())?,
+            AuctionStatus::Paused { cancels_at: None }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn remote_settlement_fails_after_unstaking() -> Result<(), BidError> {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let validator = Validator {
+            address: "validator".to_string(),
+            commission: Decimal::zero(),
+            max_commission: Decimal::one(),
+            max_change_rate: Decimal::one(),
+        };
+        deps.querier.update_staking(
+            "uatom",
+            std::slice::from_ref(&validator),
+            &[FullDelegation {
+                delegator: env.contract.address.clone(),
+                validator: validator.address.clone(),
+                amount: coin(11_500_000, "uatom"),
+                can_redelegate: coin(0, "uatom"),
+                accumulated_rewards: vec![],
+            }],
+        );
+
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            BidInstantiateMsg {
+                staking: Some(StakingConfig {
+                    validator: validator.address,
+                    rewards_to: RewardsRecipient::Bidders,
+                    unbonding_period: 60,
+                }),
+                ..Default::default()
+            },
+        )?;
+        ibc_channel_open(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_open_try("channel-0", IbcOrder::Unordered, IBC_VERSION),
+        )?;
+        ibc_channel_connect(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_connect_ack("channel-0", IbcOrder::Unordered, IBC_VERSION),
+        )?;
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("ann", &coins(12_000_000, "uatom")),
+            BidExecuteMsg::Bid {
+                referrer: None,
+                proof: None,
+                ibc_origin: None,
+                expires_at: None,
+                on_behalf_of: None,
+            },
+        )?;
+        ibc_packet_receive(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_packet_recv(
+                "channel-0",
+                &ControllerPacket::Bid {
+                    bidder: "osmo1alice".to_string(),
+                    amount: Uint128::new(13_000_000u128),
+                    proof: None,
+                },
+            )?,
+        )?;
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            BidExecuteMsg::Close {},
+        )?;
+
+        // the escrow is unbonding, the auction is cancelled rather than reopened
+        let settle = AuctionPacket::Settle {
+            bidder: "osmo1alice".to_string(),
+            amount: Uint128::new(13_000_000u128),
+            receiver: env.contract.address.to_string(),
+        };
+        let res = ibc_packet_ack(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_packet_ack(
+                "channel-0",
+                &settle,
+                IbcAcknowledgement::new(to_binary(&PacketAck::Error("refused".to_string()))?),
+            )?,
+        )?;
+        assert_eq!(
+            res.events.iter().find_map(AuctionClosed::from_event),
+            Some(AuctionClosed {
+                auction_id: env.contract.address.to_string(),
+                winner: None,
+                denom: "uatom".to_string(),
+                amount: Uint128::zero(),
+                commission: Uint128::zero(),
+            })
+        );
+        assert!(res
+            .events
+            .iter()
+            .all(|event| AuctionReopened::from_event(event).is_none()));
+        let status: AuctionStatus =
+            from_binary(&query(deps.as_ref(), env.clone(), BidQueryMsg::Status {})?)?;
+        assert_eq!(status, AuctionStatus::Cancelled);
+
+        // the remote escrow is released on the controller chain
+        let ack = ibc_packet_receive(
+            deps.as_mut(),
+            env,
+            mock_ibc_packet_recv(
+                "channel-0",
+                &ControllerPacket::Retract {
+                    bidder: "osmo1alice".to_string(),
+                },
+            )?,
+        )?;
+        assert_eq!(
+            from_binary::<PacketAck>(&ack.acknowledgement)?,
+            PacketAck::Result(to_binary(&Uint128::new(13_000_000u128))?)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn remote_proceeds_with_staking() -> Result<(), BidError> {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let validator = Validator {
+            address: "validator".to_string(),
+            commission: Decimal::zero(),
+            max_commission: Decimal::one(),
+            max_change_rate: Decimal::one(),
+        };
+        deps.querier
+            .update_staking("uatom", std::slice::from_ref(&validator), &[]);
+
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            BidInstantiateMsg {
+                staking: Some(StakingConfig {
+                    validator: validator.address.clone(),
+                    rewards_to: RewardsRecipient::Bidders,
+                    unbonding_period: 60,
+                }),
+                ..Default::default()
+            },
+        )?;
+        ibc_channel_open(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_open_try("channel-0", IbcOrder::Unordered, IBC_VERSION),
+        )?;
+        ibc_channel_connect(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_channel_connect_ack("channel-0", IbcOrder::Unordered, IBC_VERSION),
+        )?;
+
+        // ann's escrow is staked, the remote bid stays on the controller chain
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("ann", &coins(12_000_000, "uatom")),
+            BidExecuteMsg::Bid {
+                referrer: None,
+                proof: None,
+                ibc_origin: None,
+                expires_at: None,
+                on_behalf_of: None,
+            },
+        )?;
+        ibc_packet_receive(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_packet_recv(
+                "channel-0",
+                &ControllerPacket::Bid {
+                    bidder: "osmo1alice".to_string(),
+                    amount: Uint128::new(13_000_000u128),
+                    proof: None,
+                },
+            )?,
+        )?;
+
+        deps.querier.update_staking(
+            "uatom",
+            std::slice::from_ref(&validator),
+            &[FullDelegation {
+                delegator: env.contract.address.clone(),
+                validator: validator.address.clone(),
+                amount: coin(11_500_000, "uatom"),
+                can_redelegate: coin(0, "uatom"),
+                accumulated_rewards: coins(1_000_000, "uatom"),
+            }],
+        );
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            BidExecuteMsg::Close {},
+        )?;
+        let settle = AuctionPacket::Settle {
+            bidder: "osmo1alice".to_string(),
+            amount: Uint128::new(13_000_000u128),
+            receiver: env.contract.address.to_string(),
+        };
+        ibc_packet_ack(
+            deps.as_mut(),
+            env.clone(),
+            mock_ibc_packet_ack(
+                "channel-0",
+                &settle,
+                IbcAcknowledgement::new(to_binary(&PacketAck::Result(Binary::default()))?),
+            )?,
+        )?;
+
+        // the proceeds arrived while unbonding, only the withdrawn rewards are shared
+        env.block.time = env.block.time.plus_seconds(60);
+        deps.querier
+            .update_balance(env.contract.address.clone(), coins(25_500_000, "uatom"));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            BidExecuteMsg::ReleaseStake {},
+        )?;
+        let staking: StakingResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), BidQueryMsg::Staking {})?)?;
+        assert_eq!(
+            staking.rewards.map(|pool| pool.amount),
+            Some(Uint128::new(1_000_000u128))
+        );
+
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            BidExecuteMsg::CollectProceeds {},
+        )?;
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".to_string(),
+                amount: coins(13_000_000u128, "uatom"),
+            })
+        );
+        deps.querier
+            .update_balance(env.contract.address.clone(), coins(12_500_000, "uatom"));
+
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("ann", &[]),
+            BidExecuteMsg::Retract { receiver: None },
+        )?;
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "ann".to_string(),
+                amount: coins(12_500_000u128, "uatom"),
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn subscriber_hooks() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let hook_id = app.store_code(hook_contract());
+        let recorder = app
+            .instantiate_contract(hook_id, owner.clone(), &false, &[], "recorder", None)
+            .unwrap();
+        let broken = app
+            .instantiate_contract(hook_id, owner.clone(), &true, &[], "broken", None)
+            .unwrap();
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            None,
+            None,
+        )?;
+
+        let add_hook = |address: &Addr| BidExecuteMsg::AddHook {
+            address: address.to_string(),
+        };
+        assert_eq!(
+            bid.execute(&mut app, &alex, &add_hook(&recorder), &[]),
+            Err(BidError::OnlyAdmin)
+        );
+        assert_eq!(
+            bid.execute(&mut app, &owner, &add_hook(&recorder), &[]),
+            Ok(())
+        );
+        assert_eq!(
+            bid.execute(&mut app, &owner, &add_hook(&recorder), &[]),
+            Err(BidError::HookAlreadyRegistered)
+        );
+        assert_eq!(
+            bid.execute(&mut app, &owner, &add_hook(&broken), &[]),
+            Ok(())
+        );
+
+        // the broken subscriber does not stop the auction
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(bid.bid(&mut app, &ann, &coins(12_000_000, "uatom")), Ok(()));
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(alex.clone(), "uatom")?,
+            coin(99_500_000u128, "uatom")
+        );
+
+        let received: Vec<AuctionHook> =
+            app.wrap().query_wasm_smart(recorder.clone(), &Empty {})?;
+        assert_eq!(
+            received,
+            vec![
+                AuctionHook::BidPlaced {
+                    bidder: alex.to_string(),
+                    amount: Uint128::new(9_500_000u128),
+                },
+                AuctionHook::BidPlaced {
+                    bidder: ann.to_string(),
+                    amount: Uint128::new(11_500_000u128),
+                },
+                AuctionHook::Outbid {
+                    bidder: alex.to_string(),
+                    amount: Uint128::new(9_500_000u128),
+                },
+                AuctionHook::Closed {
+                    winner: Some(ann.to_string()),
+                    amount: Uint128::new(11_500_000u128),
+                },
+                AuctionHook::Retracted {
+                    bidder: alex.to_string(),
+                    amount: Uint128::new(9_500_000u128),
+                },
+            ]
+        );
+        let received: Vec<AuctionHook> = app.wrap().query_wasm_smart(broken.clone(), &Empty {})?;
+        assert!(received.is_empty());
+
+        let remove_hook = BidExecuteMsg::RemoveHook {
+            address: broken.to_string(),
+        };
+        assert_eq!(bid.execute(&mut app, &owner, &remove_hook, &[]), Ok(()));
+        assert_eq!(
+            bid.execute(&mut app, &owner, &remove_hook, &[]),
+            Err(BidError::HookNotRegistered)
+        );
+        let hooks: Vec<Addr> = app
+            .wrap()
+            .query_wasm_smart(bid.0.clone(), &BidQueryMsg::Hooks {})?;
+        assert_eq!(hooks, vec![recorder]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn winner_callback() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let dao = Addr::unchecked("dao");
+        let ann = Addr::unchecked("ann");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&dao, &ann] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let vault_id = app.store_code(vault_contract());
+        let contract_id = app.store_code(bid_contract());
+        let msg = BidInstantiateMsg {
+            notify_winner: Some(true),
+            ..Default::default()
+        };
+
+        let run_auction = |app: &mut App, fails: bool| -> StdResult<(BidContract, Addr)> {
+            let vault = app
+                .instantiate_contract(vault_id, dao.clone(), &fails, &[], "vault", None)
+                .unwrap();
+            let bid = BidContract::instantiate_with_msg(
+                app,
+                contract_id,
+                &owner,
+                "label".to_string(),
+                msg.clone(),
+            )?;
+
+            assert_eq!(bid.bid(app, &ann, &coins(10_000_000, "uatom")), Ok(()));
+            app.execute_contract(
+                dao.clone(),
+                vault.clone(),
+                &MockVaultExecuteMsg::Bid {
+                    auction: bid.0.to_string(),
+                },
+                &coins(12_000_000, "uatom"),
+            )
+            .unwrap();
+            assert_eq!(bid.close(app, &owner, &[]), Ok(()));
+            assert_eq!(bid.winner(app)?, Some(vault.to_string()));
+
+            bid.assert_reconciled(app);
+
+            Ok((bid, vault))
+        };
+
+        let (bid, vault) = run_auction(&mut app, false)?;
+        let won: Vec<(String, Uint128)> = app.wrap().query_wasm_smart(vault, &Empty {})?;
+        assert_eq!(won, vec![(bid.0.to_string(), Uint128::new(11_500_000u128))]);
+        let error: Option<String> = app
+            .wrap()
+            .query_wasm_smart(bid.0.clone(), &BidQueryMsg::WinnerCallbackError {})?;
+        assert_eq!(error, None);
+
+        // a failing callback is recorded, the auction is settled anyway
+        let (bid, vault) = run_auction(&mut app, true)?;
+        let won: Vec<(String, Uint128)> = app.wrap().query_wasm_smart(vault, &Empty {})?;
+        assert!(won.is_empty());
+        let error: Option<String> = app
+            .wrap()
+            .query_wasm_smart(bid.0.clone(), &BidQueryMsg::WinnerCallbackError {})?;
+        assert!(error.is_some());
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?,
+            coin(2 * 11_500_000u128 + 4 * 500_000u128, "uatom")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn typed_events() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                commission_policy: Some(CommissionPolicy::Refundable),
+                ..Default::default()
+            },
+        )?;
+        let auction_id = bid.0.to_string();
+
+        let mut place_bid = |sender: &Addr, amount: u128| -> Vec<BidPlaced> {
+            let msg = BidExecuteMsg::Bid {
+                referrer: None,
+                proof: None,
+                ibc_origin: None,
+                expires_at: None,
+                on_behalf_of: None,
+            };
+            bid.execute_with_response(&mut app, sender, &msg, &coins(amount, "uatom"))
+                .unwrap()
+                .events
+                .iter()
+                .filter_map(BidPlaced::from_event)
+                .collect()
+        };
+
+        assert_eq!(
+            place_bid(&alex, 10_000_000),
+            vec![BidPlaced {
+                auction_id: auction_id.clone(),
+                bidder: alex.to_string(),
+                denom: "uatom".to_string(),
+                increment: Uint128::new(9_500_000),
+                new_total: Uint128::new(9_500_000),
+                previous_leader: None,
+                commission: Uint128::new(500_000),
+                funder: None,
+            }]
+        );
+        assert_eq!(
+            place_bid(&ann, 15_000_000),
+            vec![BidPlaced {
+                auction_id: auction_id.clone(),
+                bidder: ann.to_string(),
+                denom: "uatom".to_string(),
+                increment: Uint128::new(14_500_000),
+                new_total: Uint128::new(14_500_000),
+                previous_leader: Some(alex.to_string()),
+                commission: Uint128::new(500_000),
+                funder: None,
+            }]
+        );
+        assert_eq!(
+            place_bid(&alex, 6_000_000),
+            vec![BidPlaced {
+                auction_id: auction_id.clone(),
+                bidder: alex.to_string(),
+                denom: "uatom".to_string(),
+                increment: Uint128::new(5_500_000),
+                new_total: Uint128::new(15_000_000),
+                previous_leader: Some(ann.to_string()),
+                commission: Uint128::new(500_000),
+                funder: None,
+            }]
+        );
+
+        let closed: Vec<AuctionClosed> = bid
+            .execute_with_response(&mut app, &owner, &BidExecuteMsg::Close {}, &[])
+            .unwrap()
+            .events
+            .iter()
+            .filter_map(AuctionClosed::from_event)
+            .collect();
+        assert_eq!(
+            closed,
+            vec![AuctionClosed {
+                auction_id: auction_id.clone(),
+                winner: Some(alex.to_string()),
+                denom: "uatom".to_string(),
+                amount: Uint128::new(15_000_000),
+                commission: Uint128::new(1_000_000),
+            }]
+        );
+
+        let retract = BidExecuteMsg::Retract { receiver: None };
+        let retracted: Vec<BidRetracted> = bid
+            .execute_with_response(&mut app, &ann, &retract, &[])
+            .unwrap()
+            .events
+            .iter()
+            .filter_map(BidRetracted::from_event)
+            .collect();
+        assert_eq!(
+            retracted,
+            vec![BidRetracted {
+                auction_id,
+                bidder: ann.to_string(),
+                recipient: ann.to_string(),
+                denom: "uatom".to_string(),
+                amount: Uint128::new(14_500_000),
+                commission: Uint128::new(500_000),
+            }]
+        );
+        bid.assert_reconciled(&app);
+
+        Ok(())
+    }
+
+    #[test]
+    fn metadata() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alex, coins(100_000_000u128, "uatom"))
+                .unwrap();
+        });
+
+        let contract_id = app.store_code(bid_contract());
+
+        let too_long = BidInstantiateMsg {
+            metadata: Some(AuctionMetadata {
+                title: Some("a".repeat(141)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let err = app
+            .instantiate_contract(contract_id, owner.clone(), &too_long, &[], "label", None)
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<BidError>().unwrap(),
+            BidError::InvalidMetadata {
+                reason: "title longer than 140 bytes".to_string()
+            }
+        );
+
+        let metadata = AuctionMetadata {
+            title: Some("Genesis punk #1".to_string()),
+            description: Some("First punk minted on the hub".to_string()),
+            image: Some("ipfs://bafy/1.png".to_string()),
+            animation_url: None,
+            external_url: Some("https://example.com/punks/1".to_string()),
+            attributes: Some(vec![MetadataAttribute {
+                trait_type: "background".to_string(),
+                value: "blue".to_string(),
+            }]),
+            tags: Some(vec!["pfp".to_string(), "art".to_string()]),
+        };
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                metadata: Some(metadata.clone()),
+                ..Default::default()
+            },
+        )?;
+        let query_metadata = |app: &App| -> StdResult<Option<AuctionMetadata>> {
+            app.wrap()
+                .query_wasm_smart(bid.0.clone(), &BidQueryMsg::Metadata {})
+        };
+        assert_eq!(query_metadata(&app)?, Some(metadata.clone()));
+
+        let updated = AuctionMetadata {
+            tags: Some(vec!["pfp".to_string()]),
+            ..metadata
+        };
+        let update = BidExecuteMsg::UpdateMetadata {
+            metadata: Some(updated.clone()),
+        };
+        assert_eq!(
+            bid.execute(&mut app, &alex, &update, &[]),
+            Err(BidError::OnlyOwner)
+        );
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &owner,
+                &BidExecuteMsg::UpdateMetadata {
+                    metadata: Some(AuctionMetadata {
+                        tags: Some(vec![String::new()]),
+                        ..Default::default()
+                    }),
+                },
+                &[]
+            ),
+            Err(BidError::InvalidMetadata {
+                reason: "tags must be 1 to 32 bytes".to_string()
+            })
+        );
+        assert_eq!(bid.execute(&mut app, &owner, &update, &[]), Ok(()));
+        assert_eq!(query_metadata(&app)?, Some(updated));
+
+        // locked once bidders committed to it
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(
+            bid.execute(
+                &mut app,
+                &owner,
+                &BidExecuteMsg::UpdateMetadata { metadata: None },
+                &[]
+            ),
+            Err(BidError::MetadataLocked)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn reconcile() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                commission_policy: Some(CommissionPolicy::Refundable),
+                ..Default::default()
+            },
+        )?;
+        let reconcile = |app: &App| -> StdResult<ReconcileResponse> {
+            app.wrap()
+                .query_wasm_smart(bid.0.clone(), &BidQueryMsg::Reconcile { denom: None })
+        };
+
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(bid.bid(&mut app, &ann, &coins(12_000_000, "uatom")), Ok(()));
+        assert_eq!(
+            reconcile(&app)?,
+            ReconcileResponse {
+                denom: "uatom".to_string(),
+                balance: Uint128::new(22_000_000),
+                staked: Uint128::zero(),
+                escrowed: Uint128::new(22_000_000),
+                unreleased_rewards: Uint128::zero(),
+                surplus: Uint128::zero(),
+                deficit: Uint128::zero(),
+            }
+        );
+
+        // the winning bid and its commission leave the escrow on close
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+        assert_eq!(reconcile(&app)?.escrowed, Uint128::new(10_000_000));
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(reconcile(&app)?.escrowed, Uint128::zero());
+
+        // funds nobody bid are reported
+        app.send_tokens(ann, bid.0.clone(), &coins(1_000, "uatom"))
+            .unwrap();
+        let drift = reconcile(&app)?;
+        assert_eq!(drift.surplus, Uint128::new(1_000));
+        assert_eq!(drift.deficit, Uint128::zero());
+
+        Ok(())
+    }
+
+    #[test]
+    fn staked_escrow() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+        let validator = "validator".to_string();
+
+        let mut app = App::new(|router, api, storage| {
+            router
+                .staking
+                .setup(
+                    storage,
+                    StakingInfo {
+                        bonded_denom: "uatom".to_string(),
+                        unbonding_time: 60,
+                        apr: Decimal::percent(10),
+                    },
+                )
+                .unwrap();
+            router
+                .staking
+                .add_validator(
+                    api,
+                    storage,
+                    &mock_env().block,
+                    Validator {
+                        address: validator.clone(),
+                        commission: Decimal::zero(),
+                        max_commission: Decimal::one(),
+                        max_change_rate: Decimal::one(),
+                    },
+                )
+                .unwrap();
+            for user in [&alex, &ann] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let config = |rewards_to: RewardsRecipient| StakingConfig {
+            validator: validator.clone(),
+            rewards_to,
+            unbonding_period: 60,
+        };
+
+        let err = app
+            .instantiate_contract(
+                contract_id,
+                owner.clone(),
+                &BidInstantiateMsg {
+                    denom: Some("ujuno".to_string()),
+                    staking: Some(config(RewardsRecipient::Bidders)),
+                    ..Default::default()
+                },
+                &[],
+                "label",
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<BidError>().unwrap(),
+            BidError::InvalidStaking {
+                reason: "ujuno is not the staking denom".to_string()
+            }
+        );
+
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                staking: Some(config(RewardsRecipient::Bidders)),
+                ..Default::default()
+            },
+        )?;
+        let staking = |app: &App, bid: &BidContract| -> StdResult<StakingResponse> {
+            app.wrap()
+                .query_wasm_smart(bid.0.clone(), &BidQueryMsg::Staking {})
+        };
+
+        // every bid is delegated as it comes
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(bid.bid(&mut app, &ann, &coins(20_000_000, "uatom")), Ok(()));
+        assert_eq!(staking(&app, &bid)?.staked, Uint128::new(29_000_000));
+        assert_eq!(
+            app.wrap()
+                .query_delegation(bid.0.clone(), validator.clone())?
+                .map(|delegation| delegation.amount),
+            Some(coin(29_000_000, "uatom"))
+        );
+
+        app.update_block(|block| block.time = block.time.plus_seconds(365 * 86_400));
+
+        // the winning bid waits for the unbonding
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+        let unbonding = staking(&app, &bid)?.unbonding.unwrap();
+        assert_eq!(unbonding.amount, Uint128::new(29_000_000));
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?,
+            coin(1_000_000, "uatom")
+        );
+        assert_eq!(
+            bid.retract(&mut app, &alex, &[], None),
+            Err(BidError::StakeLocked)
+        );
+        assert_eq!(
+            bid.execute(&mut app, &alex, &BidExecuteMsg::ReleaseStake {}, &[]),
+            Err(BidError::StillUnbonding)
+        );
+
+        app.update_block(|block| block.time = block.time.plus_seconds(60));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {}))
+            .unwrap();
+        assert_eq!(
+            bid.execute(&mut app, &alex, &BidExecuteMsg::ReleaseStake {}, &[]),
+            Ok(())
+        );
+
+        // rewards are shared by the amount of every bid, the winner took its share already
+        // and the last bidder gets what is left
+        let pool = staking(&app, &bid)?.rewards.unwrap();
+        assert_eq!(pool.total, Uint128::new(9_500_000));
+        let alex_share = pool.amount;
+        assert!(!alex_share.is_zero());
+        let ann_share =
+            app.wrap().query_balance(ann.clone(), "uatom")?.amount - Uint128::new(80_000_000);
+        assert_eq!(
+            ann_share,
+            (alex_share + ann_share).multiply_ratio(19_500_000u128, 29_000_000u128)
+        );
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?.amount,
+            Uint128::new(20_500_000)
+        );
+
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(alex.clone(), "uatom")?.amount,
+            Uint128::new(99_500_000) + alex_share
+        );
+        assert_eq!(
+            staking(&app, &bid)?.rewards.unwrap().amount,
+            Uint128::zero()
+        );
+
+        // a cancelled auction unbonds too, the seller can take every reward
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                staking: Some(config(RewardsRecipient::Seller)),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        app.update_block(|block| block.time = block.time.plus_seconds(365 * 86_400));
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::Cancel {}, &[]),
+            Ok(())
+        );
+        app.update_block(|block| block.time = block.time.plus_seconds(60));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {}))
+            .unwrap();
+
+        let owner_balance = app.wrap().query_balance(owner.clone(), "uatom")?.amount;
+        assert_eq!(
+            bid.execute(&mut app, &ann, &BidExecuteMsg::ReleaseStake {}, &[]),
+            Ok(())
+        );
+        assert!(app.wrap().query_balance(owner.clone(), "uatom")?.amount > owner_balance);
+        let alex_balance = app.wrap().query_balance(alex.clone(), "uatom")?.amount;
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(alex.clone(), "uatom")?.amount,
+            alex_balance + Uint128::new(9_500_000)
+        );
+
+        // a slash is shared by every staked escrow, the deficit shows until released
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                staking: Some(config(RewardsRecipient::Bidders)),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(bid.bid(&mut app, &ann, &coins(20_000_000, "uatom")), Ok(()));
+        app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+            validator: validator.clone(),
+            percentage: Decimal::percent(50),
+        }))
+        .unwrap();
+
+        // half the escrow, less the rewards withdrawn on close
+        let slashed = Uint128::new(14_499_999);
+        let execute = |app: &mut App, sender: &Addr, msg: &BidExecuteMsg, deficit| {
+            bid.execute_in_deficit(app, sender, msg, &[], deficit)
+                .map(|_| ())
+        };
+        assert_eq!(
+            execute(&mut app, &owner, &BidExecuteMsg::Close {}, slashed),
+            Ok(())
+        );
+        assert_eq!(
+            staking(&app, &bid)?.unbonding.unwrap().amount,
+            Uint128::new(14_500_000)
+        );
+        // the owner is paid once released
+        assert_eq!(
+            execute(
+                &mut app,
+                &owner,
+                &BidExecuteMsg::RenounceOwnership {},
+                slashed
+            ),
+            Err(BidError::StakeLocked)
+        );
+
+        app.update_block(|block| block.time = block.time.plus_seconds(60));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {}))
+            .unwrap();
+        assert_eq!(
+            execute(
+                &mut app,
+                &alex,
+                &BidExecuteMsg::ReleaseStake {},
+                Uint128::zero()
+            ),
+            Err(BidError::StillUnbonding)
+        );
+
+        app.update_block(|block| block.time = block.time.plus_seconds(86_400));
+        let owner_balance = app.wrap().query_balance(owner.clone(), "uatom")?.amount;
+        assert_eq!(
+            bid.execute(&mut app, &alex, &BidExecuteMsg::ReleaseStake {}, &[]),
+            Ok(())
+        );
+        // a slash leaves no rewards
+        assert_eq!(
+            staking(&app, &bid)?.rewards.unwrap().amount,
+            Uint128::zero()
+        );
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "uatom")?.amount,
+            owner_balance + Uint128::new(9_750_000)
+        );
+        // the last escrow takes what the slash left, the rewards earned before included
+        let alex_balance = app.wrap().query_balance(alex.clone(), "uatom")?.amount;
+        let left = app.wrap().query_balance(bid.0.clone(), "uatom")?.amount;
+        assert!(left >= Uint128::new(4_750_000));
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(alex, "uatom")?.amount,
+            alex_balance + left
+        );
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::RenounceOwnership {}, &[]),
+            Ok(())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn multi_denom_bids() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+        let bob = Addr::unchecked("bob");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann, &bob] {
+                router
+                    .bank
+                    .init_balance(
+                        storage,
+                        user,
+                        vec![
+                            coin(100_000_000, "uatom"),
+                            coin(100_000_000, "ujuno"),
+                            coin(100_000_000, "ustars"),
+                        ],
+                    )
+                    .unwrap();
+            }
+        });
+
+        let oracle_id = app.store_code(oracle_contract());
+        let oracle = app
+            .instantiate_contract(oracle_id, owner.clone(), &Empty {}, &[], "oracle", None)
+            .unwrap();
+        let set_price = |app: &mut App, rate: u64| {
+            let msg = MockOracleExecuteMsg::SetPrice {
+                denom: "ujuno".to_string(),
+                rate: Decimal::percent(rate),
+            };
+            app.execute_contract(owner.clone(), oracle.clone(), &msg, &[])
+                .unwrap();
+        };
+        set_price(&mut app, 200);
+
+        let contract_id = app.store_code(bid_contract());
+        let juno = AcceptedAsset {
+            denom: "ujuno".to_string(),
+            commission: Uint128::new(100_000),
+        };
+
+        // other denoms need an oracle and cannot repeat the quote one
+        let invalid = [
+            (
+                vec![juno.clone()],
+                None,
+                "an oracle is required to price other denoms",
+            ),
+            (
+                vec![AcceptedAsset {
+                    denom: "uatom".to_string(),
+                    commission: Uint128::zero(),
+                }],
+                Some(oracle.to_string()),
+                "uatom accepted twice",
+            ),
+        ];
+        for (accepted_assets, oracle, reason) in invalid {
+            let msg = BidInstantiateMsg {
+                accepted_assets: Some(accepted_assets),
+                oracle,
+                ..Default::default()
+            };
+            let err = app
+                .instantiate_contract(contract_id, owner.clone(), &msg, &[], "label", None)
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<BidError>().unwrap(),
+                BidError::InvalidAssets {
+                    reason: reason.to_string()
+                }
+            );
+        }
+
+        let bid = BidContract::instantiate_with_msg(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            BidInstantiateMsg {
+                accepted_assets: Some(vec![juno.clone()]),
+                oracle: Some(oracle.to_string()),
+                ..Default::default()
+            },
+        )?;
+        let assets: Vec<AcceptedAsset> = app
+            .wrap()
+            .query_wasm_smart(bid.0.clone(), &BidQueryMsg::AcceptedAssets {})?;
+        assert_eq!(
+            assets,
+            vec![
+                AcceptedAsset {
+                    denom: "uatom".to_string(),
+                    commission: Uint128::new(500_000),
+                },
+                juno,
+            ]
+        );
+
+        // bids are compared by their value in the quote denom
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(
+            bid.bid(&mut app, &ann, &coins(4_000_000, "ujuno")),
+            Err(BidError::BidTooLow)
+        );
+        assert_eq!(bid.bid(&mut app, &ann, &coins(5_000_000, "ujuno")), Ok(()));
+        assert_eq!(bid.highest_bidder(&app)?, Some(ann.clone()));
+        assert_eq!(bid.highest_bid(&app)?, Uint128::new(10_000_000));
+        let bid_denom: Option<String> = app.wrap().query_wasm_smart(
+            bid.0.clone(),
+            &BidQueryMsg::BidDenomFor {
+                address: ann.to_string(),
+            },
+        )?;
+        assert_eq!(bid_denom, Some("ujuno".to_string()));
+
+        // a bid is in a single accepted denom, the one of the first bid
+        assert_eq!(
+            bid.bid(&mut app, &ann, &coins(1_000_000, "uatom")),
+            Err(BidError::DenomMismatch)
+        );
+        assert_eq!(
+            bid.bid(
+                &mut app,
+                &bob,
+                &[coin(20_000_000, "uatom"), coin(20_000_000, "ujuno")]
+            ),
+            Err(BidError::MultipleDenoms)
+        );
+        assert_eq!(
+            bid.bid(&mut app, &bob, &coins(20_000_000, "ustars")),
+            Err(BidError::NotEnoughCoinForCommission)
+        );
+
+        // the lead follows the price
+        set_price(&mut app, 100);
+        assert_eq!(bid.bid(&mut app, &alex, &coins(1_000_000, "uatom")), Ok(()));
+        assert_eq!(bid.highest_bidder(&app)?, Some(alex.clone()));
+        set_price(&mut app, 300);
+        assert_eq!(bid.bid(&mut app, &ann, &coins(100_000, "ujuno")), Ok(()));
+        assert_eq!(bid.highest_bidder(&app)?, Some(ann.clone()));
+
+        // the lead is valued at bid time, a falling rate does not hand it back
+        set_price(&mut app, 100);
+        assert_eq!(bid.highest_bidder(&app)?, Some(ann.clone()));
+        assert_eq!(bid.highest_bid(&app)?, Uint128::new(5_100_000));
+
+        // the winner pays in the denom it deposited
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(owner.clone(), "ujuno")?,
+            coin(5_100_000, "ujuno")
+        );
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(alex, "uatom")?,
+            coin(99_000_000, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(owner, "uatom")?,
+            coin(1_000_000, "uatom")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn expiring_bids() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+        let bob = Addr::unchecked("bob");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann, &bob] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            None,
+            None,
+        )?;
+        let bid_until = |app: &mut App, sender: &Addr, amount: u128, expires_at| {
+            let msg = BidExecuteMsg::Bid {
+                referrer: None,
+                proof: None,
+                ibc_origin: None,
+                expires_at,
+                on_behalf_of: None,
+            };
+            bid.execute(app, sender, &msg, &coins(amount, "uatom"))
+        };
+
+        let block = app.block_info();
+        assert_eq!(
+            bid_until(
+                &mut app,
+                &ann,
+                15_000_000,
+                Some(Expiration::AtTime(block.time))
+            ),
+            Err(BidError::BidExpired)
+        );
+        assert_eq!(bid_until(&mut app, &alex, 10_000_000, None), Ok(()));
+        assert_eq!(
+            bid_until(
+                &mut app,
+                &ann,
+                15_000_000,
+                Some(Expiration::AtTime(block.time.plus_seconds(100)))
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            bid_until(
+                &mut app,
+                &bob,
+                20_000_000,
+                Some(Expiration::AtHeight(block.height + 10))
+            ),
+            Ok(())
+        );
+        assert_eq!(bid.highest_bidder(&app)?, Some(bob.clone()));
+
+        // a live bid is only refundable once the auction is over
+        assert_eq!(
+            bid.retract(&mut app, &alex, &[], None),
+            Err(BidError::BidNotClosed)
+        );
+
+        // an expired bid no longer counts, the next valid one leads
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(100);
+            block.height += 5;
+        });
+        assert_eq!(bid.highest_bidder(&app)?, Some(bob.clone()));
+        app.update_block(|block| block.height += 5);
+        assert_eq!(bid.highest_bidder(&app)?, Some(alex.clone()));
+        assert_eq!(bid.highest_bid(&app)?, Uint128::new(10_000_000));
+        let expires_at: Option<Expiration> = app.wrap().query_wasm_smart(
+            bid.0.clone(),
+            &BidQueryMsg::BidExpiresAt {
+                address: bob.to_string(),
+            },
+        )?;
+        assert_eq!(expires_at, Some(Expiration::AtHeight(block.height + 10)));
+
+        // and can be withdrawn right away, without keeping its sender out of the auction
+        assert_eq!(bid.retract(&mut app, &bob, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(bob.clone(), "uatom")?,
+            coin(99_500_000, "uatom")
+        );
+        let expires_at: Option<Expiration> = app.wrap().query_wasm_smart(
+            bid.0.clone(),
+            &BidQueryMsg::BidExpiresAt {
+                address: bob.to_string(),
+            },
+        )?;
+        assert_eq!(expires_at, None);
+        let height = app.block_info().height;
+        assert_eq!(
+            bid_until(
+                &mut app,
+                &bob,
+                20_000_000,
+                Some(Expiration::AtHeight(height + 1))
+            ),
+            Ok(())
+        );
+        assert_eq!(bid.highest_bidder(&app)?, Some(bob.clone()));
+        app.update_block(|block| block.height += 1);
+        assert_eq!(bid.highest_bidder(&app)?, Some(alex.clone()));
+
+        // a new bid renews the whole bid of its sender
+        assert_eq!(bid_until(&mut app, &ann, 1_000_000, None), Ok(()));
+        assert_eq!(bid.highest_bidder(&app)?, Some(ann.clone()));
+        assert_eq!(bid.highest_bid(&app)?, Uint128::new(16_000_000));
+
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+        assert_eq!(bid.winner(&app)?, Some(ann.to_string()));
+        assert_eq!(
+            app.wrap().query_balance(owner, "uatom")?,
+            coin(17_500_000, "uatom")
+        );
+        assert_eq!(bid.retract(&mut app, &alex, &[], None), Ok(()));
+        assert_eq!(bid.retract(&mut app, &bob, &[], None), Ok(()));
+        assert_eq!(
+            app.wrap().query_balance(bob.clone(), "uatom")?,
+            coin(99_000_000, "uatom")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn simulate_bid() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &alex, coins(100_000_000u128, "uatom"))
+                .unwrap();
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            None,
+            None,
+        )?;
+        let simulate_with = |app: &App, bidder: &Addr, funds: Vec<Coin>, referrer, expires_at| {
+            app.wrap().query_wasm_smart::<SimulateBidResponse>(
+                bid.0.clone(),
+                &BidQueryMsg::SimulateBid {
+                    bidder: bidder.to_string(),
+                    funder: None,
+                    funds,
+                    referrer,
+                    proof: None,
+                    expires_at,
+                },
+            )
+        };
+        let simulate = |app: &App, bidder: &Addr, funds: Vec<Coin>| {
+            simulate_with(app, bidder, funds, None, None)
+        };
+        let error = |err: BidError| SimulateBidResponse {
+            error: Some(err.to_string()),
+            denom: None,
+            total: Uint128::zero(),
+            commission: Uint128::zero(),
+            value: Uint128::zero(),
+            leads: false,
+            previous_leader: None,
+        };
+
+        assert_eq!(
+            simulate(&app, &alex, coins(10_000_000, "uatom"))?,
+            SimulateBidResponse {
+                error: None,
+                denom: Some("uatom".to_string()),
+                total: Uint128::new(9_500_000),
+                commission: Uint128::new(500_000),
+                value: Uint128::new(9_500_000),
+                leads: true,
+                previous_leader: None,
+            }
+        );
+        assert_eq!(bid.highest_bidder(&app)?, None);
+        assert_eq!(
+            simulate(&app, &owner, coins(10_000_000, "uatom"))?,
+            error(BidError::OwnerCannotBid)
+        );
+        assert_eq!(
+            simulate(&app, &alex, vec![])?,
+            error(BidError::NotEnoughCoinForCommission)
+        );
+        assert_eq!(
+            simulate_with(
+                &app,
+                &alex,
+                coins(10_000_000, "uatom"),
+                Some(alex.to_string()),
+                None
+            )?,
+            error(BidError::SelfReferral)
+        );
+        assert_eq!(
+            simulate_with(
+                &app,
+                &alex,
+                coins(10_000_000, "uatom"),
+                None,
+                Some(Expiration::AtHeight(app.block_info().height))
+            )?,
+            error(BidError::BidExpired)
+        );
+
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(
+            simulate(&app, &ann, coins(10_000_000, "uatom"))?,
+            error(BidError::BidTooLow)
+        );
+        assert_eq!(
+            simulate(&app, &ann, coins(20_000_000, "uatom"))?.previous_leader,
+            Some(alex.clone())
+        );
+        assert_eq!(
+            simulate(&app, &alex, coins(1_000_000, "uatom"))?.total,
+            Uint128::new(10_000_000)
+        );
+
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+        assert_eq!(
+            simulate(&app, &ann, coins(20_000_000, "uatom"))?,
+            error(BidError::BidClosed)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn bids_on_behalf() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+        let bob = Addr::unchecked("bob");
+        let custodian = Addr::unchecked("custodian");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann, &bob, &custodian, &owner] {
+                router
+                    .bank
+                    .init_balance(storage, user, coins(100_000_000u128, "uatom"))
+                    .unwrap();
+            }
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            None,
+            None,
+        )?;
+        let bid_for = |app: &mut App, funder: &Addr, bidder: &Addr, amount: u128| {
+            let msg = BidExecuteMsg::Bid {
+                referrer: None,
+                proof: None,
+                ibc_origin: None,
+                expires_at: None,
+                on_behalf_of: Some(bidder.to_string()),
+            };
+            bid.execute_with_response(app, funder, &msg, &coins(amount, "uatom"))
+        };
+
+        let approve = |app: &mut App, bidder: &Addr, funder: &Addr| {
+            let msg = BidExecuteMsg::ApproveFunder {
+                funder: funder.to_string(),
+            };
+            bid.execute(app, bidder, &msg, &[])
+        };
+        let simulate = |app: &App, funder: &Addr, bidder: &Addr, amount: u128| {
+            app.wrap()
+                .query_wasm_smart::<SimulateBidResponse>(
+                    bid.0.clone(),
+                    &BidQueryMsg::SimulateBid {
+                        bidder: bidder.to_string(),
+                        funder: Some(funder.to_string()),
+                        funds: coins(amount, "uatom"),
+                        referrer: None,
+                        proof: None,
+                        expires_at: None,
+                    },
+                )
+                .map(|simulated| simulated.error)
+        };
+
+        // the beneficiary has to accept its funder first
+        assert_eq!(
+            simulate(&app, &custodian, &alex, 10_000_000)?,
+            Some(BidError::FunderNotApproved.to_string())
+        );
+        assert_eq!(
+            bid_for(&mut app, &custodian, &alex, 10_000_000).unwrap_err(),
+            BidError::FunderNotApproved
+        );
+        assert_eq!(approve(&mut app, &alex, &custodian), Ok(()));
+        let approved: bool = app.wrap().query_wasm_smart(
+            bid.0.clone(),
+            &BidQueryMsg::FunderApproved {
+                bidder: alex.to_string(),
+                funder: custodian.to_string(),
+            },
+        )?;
+        assert!(approved);
+        assert_eq!(simulate(&app, &custodian, &alex, 10_000_000)?, None);
+
+        // the bid is credited to the beneficiary and funded by the sender
+        let placed: Vec<BidPlaced> = bid_for(&mut app, &custodian, &alex, 10_000_000)
+            .unwrap()
+            .events
+            .iter()
+            .filter_map(BidPlaced::from_event)
+            .collect();
+        assert_eq!(placed[0].bidder, alex.to_string());
+        assert_eq!(placed[0].funder, Some(custodian.to_string()));
+        assert_eq!(bid.highest_bidder(&app)?, Some(alex.clone()));
+        let funder: Option<Addr> = app.wrap().query_wasm_smart(
+            bid.0.clone(),
+            &BidQueryMsg::FunderFor {
+                address: alex.to_string(),
+            },
+        )?;
+        assert_eq!(funder, Some(custodian.clone()));
+
+        // only the first funder and the beneficiary itself can raise it
+        assert_eq!(approve(&mut app, &alex, &ann), Ok(()));
+        assert_eq!(
+            simulate(&app, &ann, &alex, 5_000_000)?,
+            Some(BidError::FunderMismatch.to_string())
+        );
+        assert_eq!(
+            bid_for(&mut app, &ann, &alex, 5_000_000).unwrap_err(),
+            BidError::FunderMismatch
+        );
+        assert!(bid_for(&mut app, &custodian, &alex, 5_000_000).is_ok());
+        assert_eq!(bid.bid(&mut app, &alex, &coins(5_000_000, "uatom")), Ok(()));
+
+        // both sides must be allowed to take part
+        assert_eq!(
+            bid_for(&mut app, &custodian, &owner, 20_000_000).unwrap_err(),
+            BidError::OwnerCannotBid
+        );
+        assert_eq!(
+            bid_for(&mut app, &owner, &ann, 20_000_000).unwrap_err(),
+            BidError::OwnerCannotBid
+        );
+        let denylist = BidExecuteMsg::UpdateDenylist {
+            add: vec![bob.to_string()],
+            remove: vec![],
+        };
+        assert_eq!(bid.execute(&mut app, &owner, &denylist, &[]), Ok(()));
+        assert_eq!(approve(&mut app, &ann, &bob), Ok(()));
+        assert_eq!(
+            simulate(&app, &bob, &ann, 30_000_000)?,
+            Some(BidError::Banned.to_string())
+        );
+        assert_eq!(
+            bid_for(&mut app, &bob, &ann, 30_000_000).unwrap_err(),
+            BidError::Banned
+        );
+
+        // the funder taking the bid back shares the refund and leaves the beneficiary
+        // free to bid again
+        assert_eq!(bid.bid(&mut app, &ann, &coins(30_000_000, "uatom")), Ok(()));
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::Pause {}, &[]),
+            Ok(())
+        );
+        assert_eq!(
+            bid.retract(&mut app, &custodian, &[], Some(alex.to_string())),
+            Ok(())
+        );
+        assert_eq!(
+            bid.execute(&mut app, &owner, &BidExecuteMsg::Unpause {}, &[]),
+            Ok(())
+        );
+        assert_eq!(
+            app.wrap().query_balance(custodian.clone(), "uatom")?,
+            coin(99_000_000, "uatom")
+        );
+        assert_eq!(
+            app.wrap().query_balance(alex.clone(), "uatom")?,
+            coin(99_500_000, "uatom")
+        );
+        assert_eq!(
+            bid.bid(&mut app, &alex, &coins(40_000_000, "uatom")),
+            Ok(())
+        );
+
+        // once revoked, the funder can no longer bid for it
+        let revoke = BidExecuteMsg::RevokeFunder {
+            funder: custodian.to_string(),
+        };
+        assert_eq!(bid.execute(&mut app, &alex, &revoke, &[]), Ok(()));
+        assert_eq!(
+            bid.execute(&mut app, &alex, &revoke, &[]),
+            Err(BidError::FunderNotApproved)
+        );
+        assert_eq!(
+            bid_for(&mut app, &custodian, &alex, 5_000_000).unwrap_err(),
+            BidError::FunderNotApproved
+        );
+
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+        assert_eq!(bid.winner(&app)?, Some(alex.to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bid_allowances() -> StdResult<()> {
+        let owner = Addr::unchecked("owner");
+        let alex = Addr::unchecked("alex");
+        let ann = Addr::unchecked("ann");
+        let bot = Addr::unchecked("bot");
+
+        let mut app = App::new(|router, _api, storage| {
+            for user in [&alex, &ann] {
+                router
+                    .bank
+                    .init_balance(
+                        storage,
+                        user,
+                        vec![coin(100_000_000, "uatom"), coin(100_000_000, "ustars")],
+                    )
+                    .unwrap();
+            }
+        });
+
+        let contract_id = app.store_code(bid_contract());
+        let bid = BidContract::instantiate(
+            &mut app,
+            contract_id,
+            &owner,
+            "label".to_string(),
+            None,
+            None,
+        )?;
+        let bid_from_deposit = |app: &mut App, sender: &Addr, amount: Coin| {
+            let msg = BidExecuteMsg::BidFromDeposit {
+                owner: alex.to_string(),
+                amount,
+                proof: None,
+                expires_at: None,
+            };
+            bid.execute(app, sender, &msg, &[])
+        };
+        let grant = |app: &mut App, agent: &Addr, max: u128, expires| {
+            let msg = BidExecuteMsg::GrantBidAllowance {
+                agent: agent.to_string(),
+                max: coin(max, "uatom"),
+                expires,
+            };
+            bid.execute(app, &alex, &msg, &[])
+        };
+        let allowance = |app: &App| -> StdResult<Option<BidAllowance>> {
+            app.wrap().query_wasm_smart(
+                bid.0.clone(),
+                &BidQueryMsg::Allowance {
+                    owner: alex.to_string(),
+                    agent: bot.to_string(),
+                },
+            )
+        };
+
+        // only accepted denoms can be deposited
+        let deposit = BidExecuteMsg::Deposit {};
+        assert_eq!(
+            bid.execute(&mut app, &alex, &deposit, &coins(1_000_000, "ustars")),
+            Err(BidError::UnacceptedDenom {
+                denom: "ustars".to_string()
+            })
+        );
+        assert_eq!(
+            bid.execute(&mut app, &alex, &deposit, &[]),
+            Err(BidError::NothingDeposited)
+        );
+        assert_eq!(
+            bid.execute(&mut app, &alex, &deposit, &coins(30_000_000, "uatom")),
+            Ok(())
+        );
+        let deposits: Vec<Coin> = app.wrap().query_wasm_smart(
+            bid.0.clone(),
+            &BidQueryMsg::Deposits {
+                address: alex.to_string(),
+            },
+        )?;
+        assert_eq!(deposits, coins(30_000_000, "uatom"));
+
+        // an agent bids for its grantor within the allowance
+        assert_eq!(
+            bid_from_deposit(&mut app, &bot, coin(10_000_000, "uatom")),
+            Err(BidError::NoAllowance)
+        );
+        assert_eq!(
+            grant(&mut app, &alex, 15_000_000, None),
+            Err(BidError::InvalidAllowance {
+                reason: "cannot grant an allowance to yourself".to_string()
+            })
+        );
+        assert_eq!(grant(&mut app, &bot, 15_000_000, None), Ok(()));
+        assert_eq!(
+            bid_from_deposit(&mut app, &bot, coin(10_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(bid.highest_bidder(&app)?, Some(alex.clone()));
+        assert_eq!(
+            allowance(&app)?,
+            Some(BidAllowance {
+                remaining: coin(5_000_000, "uatom"),
+                expires: Expiration::Never {},
+            })
+        );
+        assert_eq!(
+            bid_from_deposit(&mut app, &bot, coin(6_000_000, "uatom")),
+            Err(BidError::AllowanceExceeded)
+        );
+        assert_eq!(
+            bid_from_deposit(&mut app, &bot, coin(1_000_000, "ustars")),
+            Err(BidError::AllowanceExceeded)
+        );
+
+        // a failed bid spends nothing
+        assert_eq!(bid.bid(&mut app, &ann, &coins(15_000_000, "uatom")), Ok(()));
+        assert_eq!(
+            bid_from_deposit(&mut app, &bot, coin(5_000_000, "uatom")),
+            Err(BidError::BidTooLow)
+        );
+        assert_eq!(
+            allowance(&app)?.unwrap().remaining,
+            coin(5_000_000, "uatom")
+        );
+
+        // the owner of the deposit needs no allowance
+        assert_eq!(
+            bid_from_deposit(&mut app, &alex, coin(6_000_000, "uatom")),
+            Ok(())
+        );
+        assert_eq!(bid.highest_bidder(&app)?, Some(alex.clone()));
+
+        let revoke = BidExecuteMsg::RevokeAllowance {
+            agent: bot.to_string(),
+        };
+        assert_eq!(bid.execute(&mut app, &alex, &revoke, &[]), Ok(()));
+        assert_eq!(
+            bid.execute(&mut app, &alex, &revoke, &[]),
+            Err(BidError::NoAllowance)
+        );
+        assert_eq!(
+            bid_from_deposit(&mut app, &bot, coin(1_000_000, "uatom")),
+            Err(BidError::NoAllowance)
+        );
+
+        let height = app.block_info().height;
+        assert_eq!(
+            grant(
+                &mut app,
+                &bot,
+                1_000_000,
+                Some(Expiration::AtHeight(height))
+            ),
+            Err(BidError::InvalidAllowance {
+                reason: "already expired".to_string()
+            })
+        );
+        assert_eq!(
+            grant(
+                &mut app,
+                &bot,
+                1_000_000,
+                Some(Expiration::AtHeight(height + 1))
+            ),
+            Ok(())
+        );
+        app.update_block(|block| block.height += 1);
+        assert_eq!(
+            bid_from_deposit(&mut app, &bot, coin(1_000_000, "uatom")),
+            Err(BidError::AllowanceExpired)
+        );
+
+        // what is left of the deposit can be withdrawn
+        let withdraw = |amount: u128| BidExecuteMsg::WithdrawDeposit {
+            amount: coin(amount, "uatom"),
+        };
+        assert_eq!(
+            bid.execute(&mut app, &alex, &withdraw(14_000_001), &[]),
+            Err(BidError::InsufficientDeposit)
+        );
+        assert_eq!(
+            bid.execute(&mut app, &alex, &withdraw(14_000_000), &[]),
+            Ok(())
+        );
+        assert_eq!(
+            app.wrap().query_balance(alex.clone(), "uatom")?,
+            coin(84_000_000, "uatom")
+        );
+
+        assert_eq!(bid.close(&mut app, &owner, &[]), Ok(()));
+        assert_eq!(bid.winner(&app)?, Some(alex.to_string()));
+        assert_eq!(bid.retract(&mut app, &ann, &[], None), Ok(()));
+
+        Ok(())
+    }
 }
@@ -1,5 +1,13 @@
 pub mod contract;
 pub mod error;
+pub mod events;
+pub mod gate;
+pub mod hooks;
+pub mod ibc_hooks;
 mod integration_tests;
+pub mod merkle;
 pub mod msg;
+pub mod oracle;
+pub mod packet;
+pub mod royalties;
 mod state;
@@ -0,0 +1,96 @@
+// sha256 merkle tree over bidder addresses, used to allowlist large bidder sets.
+// Leaves and nodes are domain separated and pairs are hashed in sorted order so
+// a proof is just the list of sibling hashes, hex encoded.
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+pub fn leaf_hash(address: &str) -> Hash {
+    Sha256::new()
+        .chain_update([0u8])
+        .chain_update(address.as_bytes())
+        .finalize()
+        .into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let (first, second) = if left <= right {
+        (left, right)
+    } else {
+        (right, left)
+    };
+
+    Sha256::new()
+        .chain_update([1u8])
+        .chain_update(first)
+        .chain_update(second)
+        .finalize()
+        .into()
+}
+
+// checks the proof of `address` against a hex encoded root
+pub fn verify(root: &str, address: &str, proof: &[String]) -> Result<bool, hex::FromHexError> {
+    let root = decode_hash(root)?;
+
+    let computed = proof.iter().try_fold(leaf_hash(address), |acc, sibling| {
+        decode_hash(sibling).map(|sibling| node_hash(&acc, &sibling))
+    })?;
+
+    Ok(computed == root)
+}
+
+pub fn decode_hash(hash: &str) -> Result<Hash, hex::FromHexError> {
+    let mut decoded = [0u8; 32];
+    hex::decode_to_slice(hash, &mut decoded)?;
+    Ok(decoded)
+}
+
+pub struct MerkleTree {
+    // leaves first, root last; an odd node is carried to the next layer as is
+    layers: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn new<T: AsRef<str>>(addresses: &[T]) -> Self {
+        let mut layers = vec![addresses
+            .iter()
+            .map(|address| leaf_hash(address.as_ref()))
+            .collect::<Vec<_>>()];
+
+        while layers[layers.len() - 1].len() > 1 {
+            let next = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+
+        MerkleTree { layers }
+    }
+
+    pub fn root(&self) -> Option<String> {
+        self.layers
+            .last()
+            .and_then(|layer| layer.first())
+            .map(hex::encode)
+    }
+
+    pub fn proof(&self, address: &str) -> Option<Vec<String>> {
+        let leaf = leaf_hash(address);
+        let mut index = self.layers[0].iter().position(|hash| *hash == leaf)?;
+
+        let mut proof = vec![];
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(hex::encode(sibling));
+            }
+            index /= 2;
+        }
+
+        Some(proof)
+    }
+}
@@ -1,37 +1,476 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Coin, Decimal, Timestamp, Uint128};
+use cw_utils::Expiration;
 
 #[cw_serde]
+#[derive(Default)]
 pub struct BidInstantiateMsg {
     pub owner: Option<String>,
+    // quote denom, bids in it are compared as they are
     pub denom: Option<String>,
+    pub commission_policy: Option<CommissionPolicy>,
+    // weights must sum to 1, everything goes to the owner when unset
+    pub commission_split: Option<Vec<FeeShare>>,
+    pub proceeds_split: Option<Vec<FeeShare>>,
+    pub item: Option<AuctionItem>,
+    // pay cw2981 royalties of the item on close, off by default
+    pub pay_royalties: Option<bool>,
+    // defaults to the owner
+    pub admins: Option<Vec<String>>,
+    pub operators: Option<Vec<String>>,
+    // operators may only close once the auction is expired
+    pub expiration: Option<Expiration>,
+    // only allowlisted addresses can bid
+    pub allowlist_only: Option<bool>,
+    // hex encoded root of a `merkle::MerkleTree` of allowed bidders
+    pub merkle_root: Option<String>,
+    // contract answering `gate::GateQueryMsg`, must allow bidders and retract recipients
+    pub gate: Option<String>,
+    // seconds after which a paused auction is cancelled
+    pub max_pause_duration: Option<u64>,
+    // start paused until the IBC controller sends `ControllerPacket::OpenAuction`,
+    // unpausing also opens it
+    pub controller_opens: Option<bool>,
+    // taken from every bid, 500_000 when unset
+    pub commission: Option<Uint128>,
+    // send `hooks::WinnerCallbackMsg` on close when the winner is a contract
+    pub notify_winner: Option<bool>,
+    pub metadata: Option<AuctionMetadata>,
+    // delegate escrowed bids while the auction runs
+    pub staking: Option<StakingConfig>,
+    // other denoms bids can be placed in, each with its own commission
+    pub accepted_assets: Option<Vec<AcceptedAsset>>,
+    // contract answering `oracle::OracleQueryMsg`, required with accepted assets.
+    // A bid takes the lead when worth more than the leader at the rate of that
+    // moment, rates moving later do not hand the lead back
+    pub oracle: Option<String>,
+}
+
+#[cw_serde]
+pub enum Role {
+    Admin,
+    Operator,
+}
+
+#[cw_serde]
+pub struct RolesResponse {
+    pub admins: Vec<Addr>,
+    pub operators: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct AuctionItem {
+    pub contract: String,
+    pub token_id: String,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum CommissionPolicy {
+    // commission is sent to the owner as soon as the bid is placed
+    #[default]
+    Kept,
+    // commission is held by the contract, paid to the owner for the winner
+    // on close and refunded to the other bidders on retract
+    Refundable,
+}
+
+#[cw_serde]
+pub enum FeeRecipient {
+    Owner {},
+    // referrer given on the bid, falls back to the owner when there is none
+    Referrer {},
+    Address { address: String },
+}
+
+// bidder on another chain, `channel` is the channel on this chain it bids through
+#[cw_serde]
+pub struct IbcOrigin {
+    pub channel: String,
+    pub sender: String,
+}
+
+// listing of the auction, sizes are limited by `contract::instantiate`
+#[cw_serde]
+#[derive(Default)]
+pub struct AuctionMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub animation_url: Option<String>,
+    pub external_url: Option<String>,
+    pub attributes: Option<Vec<MetadataAttribute>>,
+    pub tags: Option<Vec<String>>,
+}
+
+#[cw_serde]
+pub struct MetadataAttribute {
+    pub trait_type: String,
+    pub value: String,
+}
+
+#[cw_serde]
+pub struct AcceptedAsset {
+    pub denom: String,
+    pub commission: Uint128,
+}
+
+#[cw_serde]
+pub struct StakingConfig {
+    pub validator: String,
+    pub rewards_to: RewardsRecipient,
+    // seconds the chain takes to unbond, payouts and refunds wait for it
+    pub unbonding_period: u64,
+}
+
+#[cw_serde]
+pub enum RewardsRecipient {
+    // pro-rata to the amount of every bid still escrowed, the rounding remainder to the seller
+    Bidders,
+    Seller,
+}
+
+// what an agent can still bid from the deposit of its grantor
+#[cw_serde]
+pub struct BidAllowance {
+    pub remaining: Coin,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct Unbonding {
+    pub amount: Uint128,
+    pub until: Timestamp,
+}
+
+// rewards owed to bidders, each gets `amount * bid / total`. Both shrink as shares are
+// paid, the last bidder gets what is left
+#[cw_serde]
+pub struct RewardPool {
+    pub amount: Uint128,
+    pub total: Uint128,
+}
+
+#[cw_serde]
+pub struct StakingResponse {
+    pub config: Option<StakingConfig>,
+    pub staked: Uint128,
+    pub unbonding: Option<Unbonding>,
+    // set once the unbonded escrow is released
+    pub rewards: Option<RewardPool>,
+}
+
+#[cw_serde]
+pub struct FeeShare {
+    pub recipient: FeeRecipient,
+    pub weight: Decimal,
 }
 
 #[cw_serde]
 pub enum BidExecuteMsg {
-    Bid {},
+    Bid {
+        referrer: Option<String>,
+        // needed when a merkle root is set and the bidder is not on the allowlist
+        proof: Option<Vec<String>>,
+        // set in the ibc-hooks memo of a cross chain bid, refunds are sent back to it
+        ibc_origin: Option<IbcOrigin>,
+        // applies to the whole bid of the sender, replaced by every new bid. Once expired
+        // the bid can be withdrawn and the bidder may bid again, but a staked escrow stays
+        // locked until the stake is released.
+        expires_at: Option<Expiration>,
+        // credits the bid to this address, which must have approved the sender as a funder,
+        // be allowed to bid and wins the item. The sender funds it and must not be the owner,
+        // banned nor rejected by the gate. Only the first funder and the bidder itself can
+        // raise it, refunds are shared by what each put in. A funder retracting it while the
+        // auction runs leaves the bidder free to bid again.
+        on_behalf_of: Option<String>,
+    },
     Close {},
-    Retract { receiver: Option<String> },
+    Retract {
+        // bidder to refund, the sender by default, the refund goes to whoever funded the bid.
+        // While the auction runs only the bidder or its funder can retract its bid.
+        receiver: Option<String>,
+    },
+    ProposeNewOwner {
+        new_owner: String,
+        expiry: Option<Expiration>,
+    },
+    // the previous owner hands over its admin role, other roles are left as they are
+    AcceptOwnership {},
+    CancelOwnershipProposal {},
+    // only once the bid is closed and paid out, the owner is gone for good
+    RenounceOwnership {},
+    AddRole {
+        address: String,
+        role: Role,
+    },
+    RemoveRole {
+        address: String,
+        role: Role,
+    },
+    SetExpiration {
+        expiration: Option<Expiration>,
+    },
+    // close without winner, every bidder can retract
+    Cancel {},
+    SetAllowlistOnly {
+        enabled: bool,
+    },
+    UpdateAllowlist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    UpdateDenylist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    SetMerkleRoot {
+        root: Option<String>,
+    },
+    SetGate {
+        address: Option<String>,
+    },
+    // blocks bids and close, bidders not leading can still retract
+    Pause {},
+    Unpause {},
+    // contract receiving `hooks::AuctionHookMsg` on bid, close and retract
+    AddHook {
+        address: String,
+    },
+    RemoveHook {
+        address: String,
+    },
+    // only until the first bid, `None` clears it
+    UpdateMetadata {
+        metadata: Option<AuctionMetadata>,
+    },
+    // unbonds the escrow of an auction cancelled by a pause running too long,
+    // close and cancel do it themselves
+    Unstake {},
+    // once unbonded, pays the winning bid and the rewards out and lets bidders retract.
+    // A day past the unbonding, a slashed escrow is released and every bid bears its share.
+    ReleaseStake {},
+    // pays out a remote winning bid once the controller transferred its escrow here
+    CollectProceeds {},
+    // credits the sender with the funds, in accepted denoms, to bid from later
+    Deposit {},
+    WithdrawDeposit {
+        amount: Coin,
+    },
+    // lets `agent` bid up to `max` from the deposit of the sender, replaces any previous grant
+    GrantBidAllowance {
+        agent: String,
+        max: Coin,
+        expires: Option<Expiration>,
+    },
+    RevokeAllowance {
+        agent: String,
+    },
+    // bids `amount` from the deposit of `owner`, by the owner itself or an agent within its
+    // allowance. Refunds go back to the owner's wallet.
+    BidFromDeposit {
+        owner: String,
+        amount: Coin,
+        proof: Option<Vec<String>>,
+        expires_at: Option<Expiration>,
+    },
+    // lets `funder` bid on behalf of the sender
+    ApproveFunder {
+        funder: String,
+    },
+    RevokeFunder {
+        funder: String,
+    },
+}
+
+#[cw_serde]
+pub enum AuctionStatus {
+    // frozen by governance, nothing can be executed
+    Frozen,
+    Open,
+    Expired,
+    Paused { cancels_at: Option<Timestamp> },
+    Closed,
+    Cancelled,
+}
+
+// outcome of a simulated bid, amounts are in its denom
+#[cw_serde]
+pub struct SimulateBidResponse {
+    // message of the error the bid would fail with, nothing else is set then
+    pub error: Option<String>,
+    pub denom: Option<String>,
+    // total bid of the bidder after this bid, commission excluded
+    pub total: Uint128,
+    pub commission: Uint128,
+    // of the total in the quote denom
+    pub value: Uint128,
+    // a valid bid always takes the lead
+    pub leads: bool,
+    pub previous_leader: Option<Addr>,
+}
+
+// balance of the auction denom against what the contract owes to bidders
+#[cw_serde]
+pub struct ReconcileResponse {
+    pub denom: String,
+    pub balance: Uint128,
+    // delegated or unbonding, unbonded funds are counted here until released
+    pub staked: Uint128,
+    // unretracted local bids, their held commission and reward share, the winning bid
+    // excluded once paid
+    pub escrowed: Uint128,
+    // what exceeds the escrow before the stake is released is paid out as rewards
+    pub unreleased_rewards: Uint128,
+    pub surplus: Uint128,
+    pub deficit: Uint128,
+}
+
+#[cw_serde]
+pub struct PendingOwnership {
+    pub new_owner: Addr,
+    pub expiry: Option<Expiration>,
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum BidQueryMsg {
+    // fails once the ownership is renounced
     #[returns(Addr)]
     GetOwner {},
+    // none once the ownership is renounced
+    #[returns(Option<Addr>)]
+    Owner {},
+    #[returns(Option<PendingOwnership>)]
+    PendingOwner {},
     #[returns(String)]
     GetDenom {},
+    // in the denom the address bid in
     #[returns(Uint128)]
     BidForAddress { address: String },
     #[returns(bool)]
     BidClosed {},
+    // valued in the quote denom at the current rate
     #[returns(Uint128)]
     HighestBid {},
+    // expired bids are skipped. The lead is decided at bid time, it is the winner
+    // on close even when another bid is worth more at the current rate
     #[returns(Option<Addr>)]
     HighestBidder {},
     #[returns(Option<Addr>)]
     BidWinner,
+    #[returns(CommissionPolicy)]
+    GetCommissionPolicy {},
+    #[returns(Uint128)]
+    CommissionFor { address: String },
+    #[returns(Vec<FeeShare>)]
+    GetCommissionSplit {},
+    #[returns(Vec<FeeShare>)]
+    GetProceedsSplit {},
+    #[returns(Option<Addr>)]
+    ReferrerFor { address: String },
+    #[returns(Option<AuctionItem>)]
+    GetItem {},
+    #[returns(bool)]
+    PayRoyalties {},
+    #[returns(RolesResponse)]
+    Roles {},
+    #[returns(Option<Expiration>)]
+    GetExpiration {},
+    #[returns(bool)]
+    AllowlistOnly {},
+    #[returns(Vec<Addr>)]
+    Allowlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(Vec<Addr>)]
+    Denylist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(Option<String>)]
+    MerkleRoot {},
+    #[returns(Option<Addr>)]
+    Gate {},
+    #[returns(AuctionStatus)]
+    Status {},
+    #[returns(Uint128)]
+    GetCommission {},
+    #[returns(Option<IbcOrigin>)]
+    IbcOriginFor { address: String },
+    #[returns(Vec<Addr>)]
+    Hooks {},
+    // error of the winner callback, if it failed
+    #[returns(Option<String>)]
+    WinnerCallbackError {},
+    #[returns(Option<AuctionMetadata>)]
+    Metadata {},
+    // of the quote denom unless another accepted denom is given
+    #[returns(ReconcileResponse)]
+    Reconcile { denom: Option<String> },
+    // the quote denom first
+    #[returns(Vec<AcceptedAsset>)]
+    AcceptedAssets {},
+    #[returns(Option<Addr>)]
+    Oracle {},
+    #[returns(Option<String>)]
+    BidDenomFor { address: String },
+    #[returns(Option<Expiration>)]
+    BidExpiresAt { address: String },
+    // set when the bid of the address was placed by someone else
+    #[returns(Option<Addr>)]
+    FunderFor { address: String },
+    #[returns(bool)]
+    FunderApproved { bidder: String, funder: String },
+    #[returns(Vec<Coin>)]
+    Deposits { address: String },
+    #[returns(Option<BidAllowance>)]
+    Allowance { owner: String, agent: String },
+    // dry run of `Bid` sent by `bidder`, or by `funder` on its behalf, with `funds` and the
+    // same options, nothing is written
+    #[returns(SimulateBidResponse)]
+    SimulateBid {
+        bidder: String,
+        funder: Option<String>,
+        funds: Vec<Coin>,
+        referrer: Option<String>,
+        proof: Option<Vec<String>>,
+        expires_at: Option<Expiration>,
+    },
+    #[returns(StakingResponse)]
+    Staking {},
 }
 
 #[cw_serde]
 pub struct BidMigrateMsg {}
+
+// only callable by the chain, overrides every role and the pause
+#[cw_serde]
+pub enum BidSudoMsg {
+    ForceClose {},
+    ForceCancel {},
+    Freeze {},
+    Unfreeze {},
+    // only applies to bids placed afterwards, held commission is unchanged
+    UpdateCommission {
+        commission: Option<Uint128>,
+        commission_split: Option<Vec<FeeShare>>,
+    },
+    // ibc-hooks callback of a cross chain refund, a failed one can be retracted again
+    IbcLifecycleComplete(IbcLifecycleComplete),
+}
+
+#[cw_serde]
+pub enum IbcLifecycleComplete {
+    IbcAck {
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool,
+    },
+    IbcTimeout {
+        channel: String,
+        sequence: u64,
+    },
+}
@@ -0,0 +1,16 @@
+// query interface of the price oracle normalising bids in other denoms to the quote denom
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Decimal;
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum OracleQueryMsg {
+    #[returns(PriceResponse)]
+    Price { denom: String },
+}
+
+// amount of the quote denom one unit of `denom` is worth
+#[cw_serde]
+pub struct PriceResponse {
+    pub rate: Decimal,
+}
@@ -0,0 +1,54 @@
+// packet protocol between an auction and the controller contract driving it
+// from another chain. Remote bids are escrowed by the controller, the auction
+// only keeps their accounting and tells the controller how they settle.
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Binary, Uint128};
+use cw_utils::Expiration;
+
+pub const IBC_VERSION: &str = "cw-bid-1";
+
+// received from the controller
+#[cw_serde]
+pub enum ControllerPacket {
+    // adds `amount` to the remote bid of `bidder`, acked with its new total.
+    // The proof is checked against the merkle root as for local bidders.
+    Bid {
+        bidder: String,
+        amount: Uint128,
+        proof: Option<Vec<String>>,
+    },
+    // releases the escrow of a losing bidder, acked with the released amount
+    Retract {
+        bidder: String,
+    },
+    // opens an auction instantiated with `controller_opens`, acked with an empty
+    // result. `expiration` replaces the one it was instantiated with when set
+    OpenAuction {
+        expiration: Option<Expiration>,
+    },
+}
+
+// sent to the controller
+#[cw_serde]
+pub enum AuctionPacket {
+    // the remote bidder won, its escrow pays for the item: the controller sends
+    // `amount` of the quote denom to `receiver`, the auction, over ICS-20 and
+    // CollectProceeds pays it out. The close is rolled back when this packet
+    // times out or is refused, or the auction cancelled once its stake unbonds
+    Settle {
+        bidder: String,
+        amount: Uint128,
+        receiver: String,
+    },
+}
+
+#[cw_serde]
+pub enum PacketAck {
+    Result(Binary),
+    Error(String),
+}
+
+// remote bids are kept under "channel/bidder", as ibc-hooks names its senders
+pub fn remote_bidder(channel: &str, bidder: &str) -> Addr {
+    Addr::unchecked(format!("{}/{}", channel, bidder))
+}
@@ -0,0 +1,28 @@
+// cw2981 royalty extension messages as exposed by the cw721 contract query endpoint
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Uint128;
+
+#[cw_serde]
+pub enum Cw721QueryMsg {
+    Extension { msg: Cw2981QueryMsg },
+}
+
+#[cw_serde]
+pub enum Cw2981QueryMsg {
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+    CheckRoyalties {},
+}
+
+#[cw_serde]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
+}
@@ -1,12 +1,236 @@
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use crate::msg::{
+    AuctionItem, AuctionMetadata, AuctionStatus, BidAllowance, CommissionPolicy, FeeShare,
+    IbcOrigin, PendingOwnership, RewardPool, Role, StakingConfig, Unbonding,
+};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, BlockInfo, Coin, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::{Deque, Item, Map};
+use cw_utils::Expiration;
 
 pub const OWNER: Item<Addr> = Item::new("OWNER");
+pub const PENDING_OWNER: Item<PendingOwnership> = Item::new("PENDING_OWNER");
+pub const ADMINS: Map<Addr, ()> = Map::new("ADMINS");
+pub const OPERATORS: Map<Addr, ()> = Map::new("OPERATORS");
+pub const EXPIRATION: Item<Option<Expiration>> = Item::new("EXPIRATION");
+pub const ALLOWLIST_ONLY: Item<bool> = Item::new("ALLOWLIST_ONLY");
+pub const ALLOWLIST: Map<Addr, ()> = Map::new("ALLOWLIST");
+pub const DENYLIST: Map<Addr, ()> = Map::new("DENYLIST");
+pub const MERKLE_ROOT: Item<Option<String>> = Item::new("MERKLE_ROOT");
+pub const GATE: Item<Option<Addr>> = Item::new("GATE");
+pub const HOOKS: Map<Addr, ()> = Map::new("HOOKS");
+pub const NOTIFY_WINNER: Item<bool> = Item::new("NOTIFY_WINNER");
+pub const WINNER_CALLBACK_ERROR: Item<Option<String>> = Item::new("WINNER_CALLBACK_ERROR");
+pub const PAUSED_AT: Item<Option<Timestamp>> = Item::new("PAUSED_AT");
+pub const MAX_PAUSE_DURATION: Item<Option<u64>> = Item::new("MAX_PAUSE_DURATION");
+pub const FROZEN: Item<bool> = Item::new("FROZEN");
 pub const DENOM: Item<String> = Item::new("DENOM");
+// commission of every accepted denom other than the quote one
+pub const ACCEPTED_ASSETS: Map<String, Uint128> = Map::new("ACCEPTED_ASSETS");
+pub const ORACLE: Item<Option<Addr>> = Item::new("ORACLE");
+pub const COMMISSION: Item<Uint128> = Item::new("COMMISSION");
+pub const COMMISSION_POLICY: Item<CommissionPolicy> = Item::new("COMMISSION_POLICY");
+pub const COMMISSION_SPLIT: Item<Vec<FeeShare>> = Item::new("COMMISSION_SPLIT");
+pub const PROCEEDS_SPLIT: Item<Vec<FeeShare>> = Item::new("PROCEEDS_SPLIT");
+pub const AUCTION_ITEM: Item<Option<AuctionItem>> = Item::new("AUCTION_ITEM");
+pub const METADATA: Item<Option<AuctionMetadata>> = Item::new("METADATA");
+pub const PAY_ROYALTIES: Item<bool> = Item::new("PAY_ROYALTIES");
+
+pub const STAKING: Item<Option<StakingConfig>> = Item::new("STAKING");
+pub const STAKED: Item<Uint128> = Item::new("STAKED");
+pub const UNBONDING: Item<Option<Unbonding>> = Item::new("UNBONDING");
+pub const REWARDS: Item<Option<RewardPool>> = Item::new("REWARDS");
+// what is left of the staked escrow after a slash, none when it came back whole
+pub const RELEASED: Item<Option<RewardPool>> = Item::new("RELEASED");
+// local escrows in the quote denom, the ones staked, kept as bids come and go
+pub const ESCROW_TOTAL: Item<EscrowTotal> = Item::new("ESCROW_TOTAL");
 
 pub const BID_BY_ADDR: Map<Addr, Uint128> = Map::new("BID_BY_ADDR");
+// only for bids not in the quote denom
+pub const BID_DENOM_BY_ADDR: Map<Addr, String> = Map::new("BID_DENOM_BY_ADDR");
 pub const BID_RETRACTED_FOR_ADDR: Map<Addr, ()> = Map::new("BID_RETRACTED_FOR_ADDR");
+pub const IBC_ORIGIN_BY_ADDR: Map<Addr, IbcOrigin> = Map::new("IBC_ORIGIN_BY_ADDR");
+// cross chain refund waiting for its packet sequence, then for its ibc-hooks callback
+pub const PENDING_IBC_REFUND: Item<IbcRefund> = Item::new("PENDING_IBC_REFUND");
+pub const IBC_REFUNDS: Map<(String, u64), IbcRefund> = Map::new("IBC_REFUNDS");
+pub const CONTROLLER_CHANNEL: Item<Option<String>> = Item::new("CONTROLLER_CHANNEL");
+// paused until the controller opens it, so a channel cannot lift a pause of the owner
+pub const AWAITING_CONTROLLER: Item<bool> = Item::new("AWAITING_CONTROLLER");
+pub const REMOTE_BIDDER_BY_ADDR: Map<Addr, IbcOrigin> = Map::new("REMOTE_BIDDER_BY_ADDR");
+// a remote winner whose escrow is still to be transferred here and paid out
+pub const REMOTE_PROCEEDS_PENDING: Item<bool> = Item::new("REMOTE_PROCEEDS_PENDING");
+pub const REFERRER_BY_ADDR: Map<Addr, Addr> = Map::new("REFERRER_BY_ADDR");
 pub const COMMISSION_BY_ADDR: Map<Addr, Option<Uint128>> = Map::new("COMMISSION_BY_ADDR");
 pub const HIGHEST_BIDDER: Item<Option<Addr>> = Item::new("HIGHEST_BIDDER");
+// every bid took the lead when placed, the last valid one leads
+pub const LEADERS: Deque<Addr> = Deque::new("LEADERS");
+pub const BID_EXPIRES_AT: Map<Addr, Expiration> = Map::new("BID_EXPIRES_AT");
+// only for bids placed on behalf of the bidder, refunds go to the funder
+pub const FUNDER_BY_ADDR: Map<Addr, Addr> = Map::new("FUNDER_BY_ADDR");
+// the part of such a bid put in by the funder, the bidder may have topped it up
+pub const FUNDED_BY_ADDR: Map<Addr, Uint128> = Map::new("FUNDED_BY_ADDR");
+// by bidder and funder, set by the bidder to accept bids placed on its behalf
+pub const APPROVED_FUNDERS: Map<(Addr, Addr), ()> = Map::new("APPROVED_FUNDERS");
+// balances deposited to bid from, by owner and denom, with their total by denom
+pub const DEPOSITS: Map<(Addr, String), Uint128> = Map::new("DEPOSITS");
+pub const TOTAL_DEPOSITS: Map<String, Uint128> = Map::new("TOTAL_DEPOSITS");
+// by owner and agent
+pub const ALLOWANCES: Map<(Addr, Addr), BidAllowance> = Map::new("ALLOWANCES");
 pub const BID_CLOSED: Item<bool> = Item::new("BID_CLOSED");
 pub const BID_WINNER: Item<Option<Addr>> = Item::new("BID_WINNER");
+
+pub fn role_members(role: &Role) -> Map<'static, Addr, ()> {
+    match role {
+        Role::Admin => ADMINS,
+        Role::Operator => OPERATORS,
+    }
+}
+
+pub fn auction_status(storage: &dyn Storage, block: &BlockInfo) -> StdResult<AuctionStatus> {
+    if FROZEN.load(storage)? {
+        return Ok(AuctionStatus::Frozen);
+    }
+
+    status_ignoring_freeze(storage, block)
+}
+
+// a pause running past the max duration cancels the auction
+pub fn status_ignoring_freeze(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+) -> StdResult<AuctionStatus> {
+    if BID_CLOSED.load(storage)? {
+        return Ok(match BID_WINNER.load(storage)? {
+            Some(_) => AuctionStatus::Closed,
+            None => AuctionStatus::Cancelled,
+        });
+    }
+
+    if let Some(paused_at) = PAUSED_AT.load(storage)? {
+        let cancels_at = MAX_PAUSE_DURATION
+            .load(storage)?
+            .map(|duration| paused_at.plus_seconds(duration));
+
+        return Ok(match cancels_at {
+            Some(cancels_at) if block.time >= cancels_at => AuctionStatus::Cancelled,
+            cancels_at => AuctionStatus::Paused { cancels_at },
+        });
+    }
+
+    let expired = EXPIRATION
+        .load(storage)?
+        .map(|expiration| expiration.is_expired(block))
+        .unwrap_or_default();
+
+    Ok(if expired {
+        AuctionStatus::Expired
+    } else {
+        AuctionStatus::Open
+    })
+}
+
+// denom the address bid in, the quote denom unless recorded otherwise
+pub fn bid_denom(storage: &dyn Storage, address: &Addr) -> StdResult<String> {
+    match BID_DENOM_BY_ADDR.may_load(storage, address.clone())? {
+        Some(denom) => Ok(denom),
+        None => DENOM.load(storage),
+    }
+}
+
+// an expired or retracted bid no longer counts
+pub fn bid_valid(storage: &dyn Storage, bidder: &Addr, block: &BlockInfo) -> StdResult<bool> {
+    // withdrawn bids are cleared
+    if BID_RETRACTED_FOR_ADDR.has(storage, bidder.clone())
+        || !BID_BY_ADDR.has(storage, bidder.clone())
+    {
+        return Ok(false);
+    }
+
+    Ok(!BID_EXPIRES_AT
+        .may_load(storage, bidder.clone())?
+        .map(|expires_at| expires_at.is_expired(block))
+        .unwrap_or_default())
+}
+
+// read only walk of the leaders, execute drops the invalid ones on top as it goes
+pub fn highest_bidder(storage: &dyn Storage, block: &BlockInfo) -> StdResult<Option<Addr>> {
+    // the lead is settled on close
+    if BID_CLOSED.load(storage)? {
+        return HIGHEST_BIDDER.load(storage);
+    }
+
+    for leader in LEADERS.iter(storage)?.rev() {
+        let leader = leader?;
+        if bid_valid(storage, &leader, block)? {
+            return Ok(Some(leader));
+        }
+    }
+
+    Ok(None)
+}
+
+// where refunds of a bid go
+pub fn refund_address(storage: &dyn Storage, bidder: &Addr) -> StdResult<Addr> {
+    Ok(FUNDER_BY_ADDR
+        .may_load(storage, bidder.clone())?
+        .unwrap_or_else(|| bidder.clone()))
+}
+
+// the escrow of a remote winner sent here for CollectProceeds, remote bids pay no commission
+pub fn pending_proceeds(storage: &dyn Storage) -> StdResult<Uint128> {
+    if !REMOTE_PROCEEDS_PENDING.load(storage)? {
+        return Ok(Uint128::zero());
+    }
+
+    match BID_WINNER.load(storage)? {
+        Some(winner) => BID_BY_ADDR.load(storage, winner),
+        None => Ok(Uint128::zero()),
+    }
+}
+
+// a refund of `amount` shared by what the funder and the bidder put in the bid
+pub fn refund_shares(
+    storage: &dyn Storage,
+    bidder: &Addr,
+    amount: Uint128,
+) -> StdResult<Vec<(Addr, Uint128)>> {
+    let shares = match FUNDER_BY_ADDR.may_load(storage, bidder.clone())? {
+        Some(funder) => {
+            let bid = BID_BY_ADDR.load(storage, bidder.clone())?;
+            let funded = FUNDED_BY_ADDR
+                .may_load(storage, bidder.clone())?
+                .unwrap_or(bid);
+            let funder_share = if bid.is_zero() {
+                amount
+            } else {
+                amount.multiply_ratio(funded, bid)
+            };
+            vec![
+                (funder, funder_share),
+                (bidder.clone(), amount - funder_share),
+            ]
+        }
+        None => vec![(bidder.clone(), amount)],
+    };
+
+    Ok(shares
+        .into_iter()
+        .filter(|(_, share)| !share.is_zero())
+        .collect())
+}
+
+// a withdrawn bid is no longer escrowed, its refund is credited as a deposit of the
+// recipient if it fails
+#[cw_serde]
+pub struct IbcRefund {
+    pub bidder: Addr,
+    pub recipient: Addr,
+    pub withdrawn: Option<Coin>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct EscrowTotal {
+    pub bid: Uint128,
+    // only held when commissions are refundable
+    pub commission: Uint128,
+}